@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+use crate::OrderNonce;
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct CancelSignedOrder<'info> {
+
+    // maker's pubkey, only the maker can burn their own nonce
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    // nonce account, once it exists any signed order with this nonce can no longer be taken
+    #[account(
+        init,
+        payer = maker,
+        space = 8 + OrderNonce::INIT_SPACE,
+        seeds = [b"nonce", maker.key().as_ref(), nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub order_nonce: Account<'info, OrderNonce>,
+
+    // system program
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CancelSignedOrder<'info> {
+
+    // marking the nonce as used so a signed order carrying it is rejected
+    pub fn cancel(&mut self, nonce: u64, bumps: &CancelSignedOrderBumps) -> Result<()> {
+        self.order_nonce.set_inner(OrderNonce {
+            maker: self.maker.key(),
+            nonce,
+            cancelled: true,
+            bump: bumps.order_nonce,
+        });
+        Ok(())
+    }
+}
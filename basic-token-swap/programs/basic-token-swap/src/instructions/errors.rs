@@ -0,0 +1,13 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum EscrowError {
+    #[msg("The signed order has expired.")]
+    OrderExpired,
+    #[msg("The signed order does not match the accounts provided.")]
+    OrderMismatch,
+    #[msg("The previous instruction is not an ed25519 signature verification.")]
+    MissingSignatureVerification,
+    #[msg("The ed25519 instruction does not verify this order for this maker.")]
+    InvalidSignature,
+}
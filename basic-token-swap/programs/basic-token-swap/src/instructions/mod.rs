@@ -1,5 +1,8 @@
 // here in mod.rs, we are exporting all the context files through mod.rs so that we only need to imprt mod.rs and we have all the contexts files available
 
+pub mod errors;
+pub use errors::*;
+
 pub mod make;
 pub use make::*;
 
@@ -8,3 +11,9 @@ pub use refund::*;
 
 pub mod take;
 pub use take::*;
+
+pub mod take_signed_order;
+pub use take_signed_order::*;
+
+pub mod cancel_signed_order;
+pub use cancel_signed_order::*;
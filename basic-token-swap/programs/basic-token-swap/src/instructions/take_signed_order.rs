@@ -0,0 +1,370 @@
+use anchor_lang::prelude::*;
+
+use anchor_lang::solana_program::{
+    ed25519_program,
+    sysvar::instructions::{self, get_instruction_relative},
+};
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{OrderNonce, SignedOrder};
+use crate::contexts::errors::EscrowError;
+
+#[derive(Accounts)]
+#[instruction(order: SignedOrder)]
+pub struct TakeSignedOrder<'info> {
+
+    // taker's pubkey, also pays for the nonce account and any missing ATAs
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    // maker's pubkey, the maker does not sign this transaction
+    #[account(
+        constraint = maker.key() == order.maker @ EscrowError::OrderMismatch
+    )]
+    pub maker: SystemAccount<'info>,
+
+    // mint of token A
+    #[account(
+        constraint = mint_a.key() == order.mint_a @ EscrowError::OrderMismatch
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    // mint of token B
+    #[account(
+        constraint = mint_b.key() == order.mint_b @ EscrowError::OrderMismatch
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    // maker's token A ATA, the delegate PDA must have been approved on it for at least `order.deposit`
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // maker's token B ATA
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // taker's token A ATA
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program
+    )]
+    pub taker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // taker's token B ATA
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program
+    )]
+    pub taker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // PDA the maker approves as delegate on their token A ATA, it signs the pull of token A
+    /// CHECK: only used as a signer, the seeds tie it to the maker
+    #[account(
+        seeds = [b"delegate", maker.key().as_ref()],
+        bump
+    )]
+    pub delegate: UncheckedAccount<'info>,
+
+    // nonce account, creating it fails if the order was already taken or cancelled
+    #[account(
+        init,
+        payer = taker,
+        space = 8 + OrderNonce::INIT_SPACE,
+        seeds = [b"nonce", maker.key().as_ref(), order.nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub order_nonce: Account<'info, OrderNonce>,
+
+    // instructions sysvar, used to read the ed25519 instruction before this one
+    /// CHECK: the address is checked against the instructions sysvar id
+    #[account(address = instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    // associated token program
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    // token program
+    pub token_program: Interface<'info, TokenInterface>,
+
+    // system program
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TakeSignedOrder<'info> {
+
+    // checking that the maker signed this exact order and that it can still be taken
+    pub fn verify(&mut self, order: &SignedOrder, bumps: &TakeSignedOrderBumps) -> Result<()> {
+
+        // the order must not be expired
+        let clock = Clock::get()?;
+        require!(clock.unix_timestamp < order.expires_at, EscrowError::OrderExpired);
+
+        // the instruction right before this one must be the ed25519 precompile verifying the maker's signature
+        let ed25519_ix = get_instruction_relative(-1, &self.instructions.to_account_info())?;
+        require_keys_eq!(
+            ed25519_ix.program_id,
+            ed25519_program::ID,
+            EscrowError::MissingSignatureVerification
+        );
+        verify_ed25519_data(&ed25519_ix.data, &order.maker, &order.message())?;
+
+        // recording the nonce so the same order can never be taken twice
+        self.order_nonce.set_inner(OrderNonce {
+            maker: order.maker,
+            nonce: order.nonce,
+            cancelled: false,
+            bump: bumps.order_nonce,
+        });
+
+        Ok(())
+    }
+
+    // transfering token B from taker to maker
+    pub fn deposit(&mut self, order: &SignedOrder) -> Result<()> {
+
+        // step 1: define all the accounts using the TransferChecked method
+        let transfer_accounts = TransferChecked {
+            from: self.taker_ata_b.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+            to: self.maker_ata_b.to_account_info(),
+            authority: self.taker.to_account_info(),
+        };
+
+        // step 2: setup the cpi context for cpi to the token program
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+
+        // step 3: call the cpi
+        transfer_checked(cpi_ctx, order.receive, self.mint_b.decimals)
+    }
+
+    // pulling token A from the maker to the taker using the delegate approval
+    pub fn withdraw(&mut self, order: &SignedOrder, bumps: &TakeSignedOrderBumps) -> Result<()> {
+
+        // the delegate PDA signs instead of the maker
+        let maker_key = self.maker.key();
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"delegate",
+            maker_key.as_ref(),
+            &[bumps.delegate],
+        ]];
+
+        // step 1: define all the accounts using the TransferChecked method
+        let transfer_accounts = TransferChecked {
+            from: self.maker_ata_a.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.taker_ata_a.to_account_info(),
+            authority: self.delegate.to_account_info(),
+        };
+
+        // step 2: setup the cpi context for cpi to the token program
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            transfer_accounts,
+            &signer_seeds,
+        );
+
+        // step 3: call the cpi
+        transfer_checked(cpi_ctx, order.deposit, self.mint_a.decimals)
+    }
+}
+
+// layout of the ed25519 precompile instruction data:
+// [num_signatures: u8, padding: u8, then one 14 byte offsets block per signature]
+// each offsets block is seven u16 values:
+// signature_offset, signature_ix_index, public_key_offset, public_key_ix_index,
+// message_offset, message_size, message_ix_index
+const SIGNATURE_OFFSETS_START: usize = 2;
+const SIGNATURE_OFFSETS_LEN: usize = 14;
+const PUBKEY_LEN: usize = 32;
+const SIGNATURE_LEN: usize = 64;
+
+// the precompile already checked the signature, here we only check that it signed what we expect
+fn verify_ed25519_data(data: &[u8], maker: &Pubkey, message: &[u8]) -> Result<()> {
+    require!(
+        data.len() >= SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_LEN && data[0] == 1,
+        EscrowError::InvalidSignature
+    );
+
+    let read_u16 = |index: usize| -> usize {
+        let at = SIGNATURE_OFFSETS_START + index * 2;
+        u16::from_le_bytes([data[at], data[at + 1]]) as usize
+    };
+
+    let signature_offset = read_u16(0);
+    let signature_ix_index = read_u16(1);
+    let public_key_offset = read_u16(2);
+    let public_key_ix_index = read_u16(3);
+    let message_offset = read_u16(4);
+    let message_size = read_u16(5);
+    let message_ix_index = read_u16(6);
+
+    // every offset must point into the ed25519 instruction itself (u16::MAX), not into another instruction
+    require!(
+        signature_ix_index == u16::MAX as usize
+            && public_key_ix_index == u16::MAX as usize
+            && message_ix_index == u16::MAX as usize,
+        EscrowError::InvalidSignature
+    );
+
+    require!(
+        signature_offset + SIGNATURE_LEN <= data.len()
+            && public_key_offset + PUBKEY_LEN <= data.len()
+            && message_offset + message_size <= data.len(),
+        EscrowError::InvalidSignature
+    );
+
+    // the signer must be the maker and the signed bytes must be this order
+    require!(
+        &data[public_key_offset..public_key_offset + PUBKEY_LEN] == maker.as_ref(),
+        EscrowError::InvalidSignature
+    );
+    require!(
+        &data[message_offset..message_offset + message_size] == message,
+        EscrowError::InvalidSignature
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Keypair, PublicKey, SecretKey};
+    use solana_sdk::ed25519_instruction::new_ed25519_instruction;
+
+    fn keypair(seed: u8) -> Keypair {
+        let secret = SecretKey::from_bytes(&[seed; 32]).unwrap();
+        let public = PublicKey::from(&secret);
+        Keypair { secret, public }
+    }
+
+    fn order(maker: Pubkey, nonce: u64) -> SignedOrder {
+        SignedOrder {
+            maker,
+            mint_a: Pubkey::new_unique(),
+            mint_b: Pubkey::new_unique(),
+            deposit: 1_000,
+            receive: 500,
+            expires_at: 1_700_000_000,
+            nonce,
+        }
+    }
+
+    // a real ed25519 precompile instruction signing `order`, with the maker's key
+    fn signed(keypair: &Keypair, order: &SignedOrder) -> Vec<u8> {
+        new_ed25519_instruction(keypair, &order.message()).data
+    }
+
+    fn maker_of(keypair: &Keypair) -> Pubkey {
+        Pubkey::new_from_array(keypair.public.to_bytes())
+    }
+
+    // overwrites the `field`th u16 of the offsets block
+    fn set_offset(data: &mut [u8], field: usize, value: u16) {
+        let at = SIGNATURE_OFFSETS_START + field * 2;
+        data[at..at + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn rejected(data: &[u8], maker: &Pubkey, message: &[u8]) -> bool {
+        verify_ed25519_data(data, maker, message).unwrap_err() == EscrowError::InvalidSignature.into()
+    }
+
+    #[test]
+    fn accepts_the_maker_signing_the_order() {
+        let keypair = keypair(1);
+        let order = order(maker_of(&keypair), 7);
+
+        assert!(verify_ed25519_data(&signed(&keypair, &order), &order.maker, &order.message()).is_ok());
+    }
+
+    #[test]
+    fn rejects_anything_but_one_signature() {
+        let keypair = keypair(1);
+        let order = order(maker_of(&keypair), 7);
+        let mut data = signed(&keypair, &order);
+
+        for count in [0, 2] {
+            data[0] = count;
+            assert!(rejected(&data, &order.maker, &order.message()));
+        }
+    }
+
+    #[test]
+    fn rejects_offsets_into_another_instruction() {
+        let keypair = keypair(1);
+        let order = order(maker_of(&keypair), 7);
+
+        // signature, public key and message instruction indices
+        for field in [1, 3, 6] {
+            let mut data = signed(&keypair, &order);
+            set_offset(&mut data, field, 0);
+            assert!(rejected(&data, &order.maker, &order.message()));
+        }
+    }
+
+    #[test]
+    fn rejects_out_of_range_offsets() {
+        let keypair = keypair(1);
+        let order = order(maker_of(&keypair), 7);
+        let len = signed(&keypair, &order).len() as u16;
+
+        // signature offset, public key offset, message offset and message size
+        for (field, value) in [(0, len - 63), (2, len - 31), (4, len), (5, len)] {
+            let mut data = signed(&keypair, &order);
+            set_offset(&mut data, field, value);
+            assert!(rejected(&data, &order.maker, &order.message()));
+        }
+
+        // too short to hold the offsets block at all
+        assert!(rejected(&[1, 0, 0], &order.maker, &order.message()));
+        assert!(rejected(&[], &order.maker, &order.message()));
+    }
+
+    #[test]
+    fn rejects_a_signature_by_someone_else() {
+        let maker = keypair(1);
+        let order = order(maker_of(&maker), 7);
+
+        let data = signed(&keypair(2), &order);
+        assert!(rejected(&data, &order.maker, &order.message()));
+    }
+
+    #[test]
+    fn rejects_a_different_or_truncated_message() {
+        let keypair = keypair(1);
+        let order = order(maker_of(&keypair), 7);
+        let data = signed(&keypair, &order);
+
+        // the same maker signing another order does not authorize this one
+        let other = SignedOrder { nonce: 8, ..order.clone() };
+        assert!(rejected(&signed(&keypair, &other), &order.maker, &order.message()));
+
+        // a shorter message size signs only a prefix of the order
+        let mut prefix = data.clone();
+        set_offset(&mut prefix, 5, order.message().len() as u16 - 1);
+        assert!(rejected(&prefix, &order.maker, &order.message()));
+
+        // data cut before the end of the message
+        assert!(rejected(&data[..data.len() - 1], &order.maker, &order.message()));
+    }
+}
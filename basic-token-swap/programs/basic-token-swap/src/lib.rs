@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 
+#[path = "instructions/mod.rs"]
 pub mod contexts;
 use contexts::*;
 
@@ -25,4 +26,16 @@ pub mod basic_token_swap {
         ctx.accounts.deposit()?;
         ctx.accounts.withdraw_and_close_vault()
     }
+
+    // taker fills an order the maker signed off-chain, token A is pulled through the maker's delegate approval
+    pub fn take_signed_order(ctx: Context<TakeSignedOrder>, order: SignedOrder) -> Result<()> {
+        ctx.accounts.verify(&order, &ctx.bumps)?;
+        ctx.accounts.deposit(&order)?;
+        ctx.accounts.withdraw(&order, &ctx.bumps)
+    }
+
+    // maker burns a nonce so the signed order carrying it can never be taken
+    pub fn cancel_signed_order(ctx: Context<CancelSignedOrder>, nonce: u64) -> Result<()> {
+        ctx.accounts.cancel(nonce, &ctx.bumps)
+    }
 }
@@ -13,6 +13,36 @@ pub struct Escrow {        // this is the struct of the account, we are defining
     pub bump: u8,          // this is the bump of the account
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]   // an order signed off-chain by the maker, never stored on-chain
+pub struct SignedOrder {
+    pub maker: Pubkey,     // this is the maker who signed the order
+    pub mint_a: Pubkey,    // this is the mint of the token A the maker is giving
+    pub mint_b: Pubkey,    // this is the mint of the token B the maker wants
+    pub deposit: u64,      // this is the amount of token A pulled from the maker
+    pub receive: u64,      // this is the amount of token B that the maker is receiving
+    pub expires_at: i64,   // this is the unix timestamp after which the order can no longer be taken
+    pub nonce: u64,        // this is a maker-chosen number that makes every order unique
+}
+
+impl SignedOrder {
+    // the exact bytes the maker signs with ed25519
+    // the program id is prefixed so an order can not be replayed against another deployment
+    pub fn message(&self) -> Vec<u8> {
+        let mut message = crate::ID.to_bytes().to_vec();
+        message.extend_from_slice(&self.try_to_vec().unwrap());
+        message
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct OrderNonce {    // marks a signed order nonce as used, created when the order is taken or cancelled
+    pub maker: Pubkey,     // this is the maker the nonce belongs to
+    pub nonce: u64,        // this is the nonce of the signed order
+    pub cancelled: bool,   // true if the maker cancelled the order, false if it was taken
+    pub bump: u8,          // this is the bump of the account
+}
+
 
 
 
@@ -0,0 +1,130 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::{create_idempotent, AssociatedToken, Create},
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{Escrow, EscrowStatus, ItemKind, TokenData};
+use crate::contexts::{
+    errors::EscrowError,
+    validate::{check_nft, token_balance, vault_address},
+};
+
+#[derive(Accounts)]
+pub struct AddOfferedItem<'info> {
+
+    // maker's pubkey, pays for the vault and the extra escrow space
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    // escrow account, grown by one offered item
+    #[account(
+        mut,
+        has_one = maker,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        realloc = Escrow::space(escrow.offered.len() + 1, escrow.expected.len()),
+        realloc::payer = maker,
+        realloc::zero = false
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    // mint of the offered token
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    // maker's ATA for the offered token
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata: InterfaceAccount<'info, TokenAccount>,
+
+    // vault for the offered token, created if nobody has created it ahead of time
+    /// CHECK: must be the escrow's ATA for the mint, created by this instruction if missing
+    #[account(
+        mut,
+        address = vault_address(&escrow.key(), &mint.key(), &token_program.key()) @ EscrowError::VaultMismatch
+    )]
+    pub vault: UncheckedAccount<'info>,
+
+    // associated token program
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    // token program
+    pub token_program: Interface<'info, TokenInterface>,
+
+    // system program
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddOfferedItem<'info> {
+
+    // deposit one more offered token into its own vault and record it on the escrow
+    pub fn add(&mut self, amount: u64, kind: ItemKind) -> Result<()> {
+        require!(
+            self.escrow.status == EscrowStatus::Building,
+            EscrowError::InvalidEscrowStatus
+        );
+        require!(amount > 0, EscrowError::ZeroAmount);
+
+        // an offered NFT is a single named token, and it makes the basket indivisible
+        match kind {
+            ItemKind::Token => {}
+            ItemKind::Nft => {
+                require!(amount == 1, EscrowError::InvalidNftAmount);
+                check_nft(&self.mint)?;
+                require!(self.escrow.total_units == 1, EscrowError::NftBasketNotDivisible);
+            }
+            ItemKind::Collection => return err!(EscrowError::CollectionNotOffered),
+        }
+        require!(
+            !self.escrow.offered.iter().any(|token| token.mint == self.mint.key()),
+            EscrowError::DuplicateMint
+        );
+
+        let create_accounts = Create {
+            payer: self.maker.to_account_info(),
+            associated_token: self.vault.to_account_info(),
+            authority: self.escrow.to_account_info(),
+            mint: self.mint.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.associated_token_program.to_account_info(), create_accounts);
+
+        create_idempotent(cpi_ctx)?;
+
+        let transfer_accounts = TransferChecked {
+            from: self.maker_ata.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.maker.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+
+        // a vault that already existed may hold tokens sent to it ahead of time, only the deposit itself is checked
+        let before = token_balance(&self.vault)?;
+        transfer_checked(cpi_ctx, amount, self.mint.decimals)?;
+
+        // the vault must receive exactly what will be recorded,
+        // a mint that charges transfer fees would leave it short
+        require!(
+            token_balance(&self.vault)?.checked_sub(before) == Some(amount),
+            EscrowError::VaultBalanceMismatch
+        );
+
+        self.escrow.offered.push(TokenData {
+            mint: self.mint.key(),
+            amount,
+            kind,
+        });
+        self.escrow.offered_remaining.push(amount);
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::{Escrow, EscrowStatus, TAKE_LOCK_DURATION};
+use crate::contexts::errors::EscrowError;
+
+#[derive(Accounts)]
+pub struct BeginTake<'info> {
+
+    // the taker who is going to settle the basket in chunks
+    pub taker: Signer<'info>,
+
+    // the original escrow creator (maker)
+    /// CHECK: only used to derive the escrow PDA
+    pub maker: UncheckedAccount<'info>,
+
+    // escrow account being locked
+    #[account(
+        mut,
+        has_one = maker,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+}
+
+impl<'info> BeginTake<'info> {
+
+    // lock the basket to this taker for a fill of `units` paid with `payment`,
+    // so no one else can take or refund it while the chunks go through,
+    // a lock that expired before settling anything is taken over
+    pub fn lock(&mut self, units: u64, payment: Option<u16>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            self.escrow.is_open_for_take(now),
+            EscrowError::InvalidEscrowStatus
+        );
+        self.escrow.check_units(units)?;
+        self.escrow.payment_items(payment)?;
+
+        let expires_at = now
+            .checked_add(TAKE_LOCK_DURATION)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        self.escrow.status = EscrowStatus::Locked {
+            taker: self.taker.key(),
+            expires_at,
+            units,
+            payment,
+        };
+        self.escrow.settled = 0;
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum EscrowError {
+    #[msg("The mint account does not match the escrow's token mint.")]
+    MintMismatch,
+    #[msg("The mint account is not a mint owned by the token program.")]
+    InvalidMint,
+    #[msg("The token account is not owned by the token program.")]
+    TokenProgramMismatch,
+    #[msg("The account is not a token account.")]
+    InvalidTokenAccount,
+    #[msg("The token account holds a different mint.")]
+    TokenAccountMintMismatch,
+    #[msg("The token account belongs to the wrong authority.")]
+    AuthorityMismatch,
+    #[msg("The vault is not the escrow's associated token account for this mint.")]
+    VaultMismatch,
+    #[msg("The number of remaining accounts does not match the escrow's baskets.")]
+    AccountCountMismatch,
+    #[msg("A token account in remaining accounts must be writable.")]
+    AccountNotWritable,
+    #[msg("The authority of the source token account must sign.")]
+    MissingSigner,
+    #[msg("The escrow is not in the right stage for this instruction.")]
+    InvalidEscrowStatus,
+    #[msg("A basket needs at least one offered and one expected item.")]
+    EmptyBasket,
+    #[msg("Only the taker holding the lock can pay for the staged take.")]
+    NotLockHolder,
+    #[msg("The staged take lock has not expired.")]
+    LockNotExpired,
+    #[msg("The staged take has been paid for, it can only be finished.")]
+    TakeInProgress,
+    #[msg("The staged take lock expired before its first chunk settled.")]
+    LockExpired,
+    #[msg("Units must be at least one and no more than the basket has left.")]
+    InvalidUnits,
+    #[msg("Pick a payment alternative for an any-of escrow and none for an all-of escrow.")]
+    InvalidPaymentChoice,
+    #[msg("Basket amounts must be greater than zero.")]
+    ZeroAmount,
+    #[msg("A mint appears more than once in the basket.")]
+    DuplicateMint,
+    #[msg("The vault balance does not match the amount recorded on the escrow.")]
+    VaultBalanceMismatch,
+    #[msg("The mint is not an NFT, it needs 0 decimals and a supply of 1.")]
+    NotAnNft,
+    #[msg("NFT and collection items must have an amount of 1.")]
+    InvalidNftAmount,
+    #[msg("Collection items can only be expected, not offered.")]
+    CollectionNotOffered,
+    #[msg("A basket with NFTs cannot be split into more than one unit.")]
+    NftBasketNotDivisible,
+    #[msg("The metadata account is not the Metaplex metadata of this NFT.")]
+    InvalidMetadata,
+    #[msg("The NFT is not a verified member of the expected collection.")]
+    CollectionMismatch,
+    #[msg("The token account is not the owner's associated token account for this mint.")]
+    NotAssociatedTokenAccount,
+    #[msg("Programmable NFTs are not supported, they can only be moved through the token metadata program.")]
+    ProgrammableNftNotSupported,
+    #[msg("The first chunk of a staged take must settle the whole payment.")]
+    PaymentSplitAcrossChunks,
+}
@@ -1,11 +1,12 @@
 use anchor_lang::prelude::*;
 
 use anchor_spl::{
-    associated_token::AssociatedToken,
+    associated_token::{create_idempotent, AssociatedToken, Create},
     token_interface::{transfer_checked, TokenInterface, TransferChecked},
 };
 
-use crate::{Escrow, TokenData};
+use crate::{Escrow, EscrowStatus, ExpectedMode, TokenData};
+use crate::contexts::{errors::EscrowError, validate::token_balance};
 
 #[derive(Accounts)]
 #[instruction(seed: u64)]
@@ -43,18 +44,51 @@ impl<'info> Make<'info> {
         seed: u64,
         offered: Vec<TokenData>,
         expected: Vec<TokenData>,
+        expected_mode: ExpectedMode,
+        units: u64,
         bump: u8,
     ) -> Result<()> {
+        require!(units > 0, EscrowError::InvalidUnits);
+
         self.escrow.set_inner(Escrow {
             seed,                                      // seed for PDA
             maker: self.maker.key(),                   // pubkey of escrow creator
+            offered_remaining: offered.iter().map(|token| token.amount).collect(),
+            expected_remaining: expected.iter().map(|token| token.amount).collect(),
             offered,                                   // list of tokens being deposited
             expected,                                  // list of tokens expected
+            expected_mode,                             // pay all expected tokens or one of them
+            total_units: units,                        // units the basket can be filled in
+            remaining_units: units,                    // nothing taken yet
+            status: EscrowStatus::Sealed,              // everything was deposited in this transaction
+            settled: 0,                                // nothing settled yet
             bump,                                      // bump for escrow PDA
         });
         Ok(())
     }
 
+    // helper function to create the vault for one offered mint as the escrow's ATA
+    // the vault address was already checked to be that ATA, so it can never be an account the maker controls
+    // idempotent so a vault someone created ahead of the maker can't block the make
+    pub fn create_vault(
+        &self,
+        mint: &AccountInfo<'info>,
+        vault: &AccountInfo<'info>,
+    ) -> Result<()> {
+        let create_accounts = Create {
+            payer: self.maker.to_account_info(),
+            associated_token: vault.clone(),
+            authority: self.escrow.to_account_info(),
+            mint: mint.clone(),
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.associated_token_program.to_account_info(), create_accounts);
+
+        create_idempotent(cpi_ctx)
+    }
+
     // helper function to transfer token from maker to vault
     pub fn deposit_single_token(
         &self,
@@ -73,6 +107,16 @@ impl<'info> Make<'info> {
 
         let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
 
-        transfer_checked(cpi_ctx, amount, decimals)
+        // a vault that already existed may hold tokens sent to it ahead of time, only the deposit itself is checked
+        let before = token_balance(to)?;
+        transfer_checked(cpi_ctx, amount, decimals)?;
+
+        // the vault must receive exactly what will be recorded,
+        // a mint that charges transfer fees would leave it short
+        require!(
+            token_balance(to)?.checked_sub(before) == Some(amount),
+            EscrowError::VaultBalanceMismatch
+        );
+        Ok(())
     }
 }
@@ -0,0 +1,325 @@
+// reads the parts of a Metaplex token metadata account that collection items need,
+// parsed by hand from its borsh layout so the program does not pull in the whole metadata crate
+
+use anchor_lang::prelude::*;
+
+use crate::contexts::errors::EscrowError;
+
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey = pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+// account discriminator Metaplex uses for metadata v1
+const METADATA_V1_KEY: u8 = 4;
+
+// a creator is a pubkey, a verified flag and a share
+const CREATOR_LEN: usize = 32 + 1 + 1;
+
+// token standards whose token accounts stay frozen and only move through the metadata program
+const PROGRAMMABLE_NON_FUNGIBLE: u8 = 4;
+const PROGRAMMABLE_NON_FUNGIBLE_EDITION: u8 = 5;
+
+// the fields of a metadata account collection items are checked against
+#[derive(Debug, PartialEq)]
+struct MetadataFields {
+    mint: Pubkey,
+    token_standard: Option<u8>,
+    collection: Option<Pubkey>,     // set only when the collection is verified
+}
+
+// the metadata must be the Metaplex metadata PDA of `mint`
+// and name `collection` as its verified collection,
+// programmable NFTs are rejected as a plain token transfer can't move them
+pub fn check_collection(metadata: &AccountInfo, mint: &Pubkey, collection: &Pubkey) -> Result<()> {
+    require_keys_eq!(
+        *metadata.owner,
+        TOKEN_METADATA_PROGRAM_ID,
+        EscrowError::InvalidMetadata
+    );
+
+    let (address, _) = Pubkey::find_program_address(
+        &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &TOKEN_METADATA_PROGRAM_ID,
+    );
+    require_keys_eq!(metadata.key(), address, EscrowError::InvalidMetadata);
+
+    let data = metadata.data.borrow();
+    let fields = read_metadata(&data).ok_or(error!(EscrowError::InvalidMetadata))?;
+    require_keys_eq!(fields.mint, *mint, EscrowError::InvalidMetadata);
+
+    require!(
+        !matches!(
+            fields.token_standard,
+            Some(PROGRAMMABLE_NON_FUNGIBLE | PROGRAMMABLE_NON_FUNGIBLE_EDITION)
+        ),
+        EscrowError::ProgrammableNftNotSupported
+    );
+
+    require!(
+        fields.collection == Some(*collection),
+        EscrowError::CollectionMismatch
+    );
+
+    Ok(())
+}
+
+// walks key, update authority, mint, the data struct and the optional fields before the collection,
+// returns the mint, the token standard and the collection key if the collection is verified
+// metadata accounts are zero padded, so optional fields past the end of older layouts read as None
+fn read_metadata(data: &[u8]) -> Option<MetadataFields> {
+    let mut reader = Reader { data, offset: 0 };
+
+    if reader.u8()? != METADATA_V1_KEY {
+        return None;
+    }
+    reader.skip(32)?;                     // update authority
+    let mint = reader.pubkey()?;
+
+    reader.skip_string()?;                // name
+    reader.skip_string()?;                // symbol
+    reader.skip_string()?;                // uri
+    reader.skip(2)?;                      // seller fee basis points
+    if reader.option()? {                 // creators
+        let creators = reader.u32()? as usize;
+        reader.skip(creators.checked_mul(CREATOR_LEN)?)?;
+    }
+    reader.skip(2)?;                      // primary sale happened, is mutable
+    if reader.option()? {                 // edition nonce
+        reader.skip(1)?;
+    }
+    let token_standard = if reader.option()? {
+        Some(reader.u8()?)
+    } else {
+        None
+    };
+
+    let collection = if reader.option()? {
+        let verified = reader.u8()? == 1;
+        let key = reader.pubkey()?;
+        verified.then_some(key)
+    } else {
+        None
+    };
+
+    Some(MetadataFields { mint, token_standard, collection })
+}
+
+struct Reader<'d> {
+    data: &'d [u8],
+    offset: usize,
+}
+
+impl<'d> Reader<'d> {
+    fn take(&mut self, len: usize) -> Option<&'d [u8]> {
+        let end = self.offset.checked_add(len)?;
+        let bytes = self.data.get(self.offset..end)?;
+        self.offset = end;
+        Some(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> Option<()> {
+        self.take(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|bytes| bytes[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn option(&mut self) -> Option<bool> {
+        Some(self.u8()? == 1)
+    }
+
+    fn pubkey(&mut self) -> Option<Pubkey> {
+        Some(Pubkey::new_from_array(self.take(32)?.try_into().ok()?))
+    }
+
+    fn skip_string(&mut self) -> Option<()> {
+        let len = self.u32()? as usize;
+        self.skip(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use mpl_token_metadata::{
+        accounts::Metadata,
+        types::{Collection, Creator, Key, ProgrammableConfig, TokenStandard},
+        MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH,
+    };
+
+    // size the metadata program allocates for every metadata account
+    const MAX_METADATA_LEN: usize = 679;
+
+    // the metadata program pads name, symbol and uri with zero bytes to their maximum length
+    fn puffed(value: &str, len: usize) -> String {
+        format!("{value}{}", "\0".repeat(len - value.len()))
+    }
+
+    fn nft(mint: Pubkey, collection: Option<Collection>) -> Metadata {
+        Metadata {
+            key: Key::MetadataV1,
+            update_authority: Pubkey::new_unique(),
+            mint,
+            name: puffed("Escrow Test #1", MAX_NAME_LENGTH),
+            symbol: puffed("ESC", MAX_SYMBOL_LENGTH),
+            uri: puffed("https://arweave.net/escrow-test-1.json", MAX_URI_LENGTH),
+            seller_fee_basis_points: 500,
+            creators: Some(vec![
+                Creator { address: Pubkey::new_unique(), verified: true, share: 60 },
+                Creator { address: Pubkey::new_unique(), verified: false, share: 40 },
+            ]),
+            primary_sale_happened: true,
+            is_mutable: true,
+            edition_nonce: Some(254),
+            token_standard: Some(TokenStandard::NonFungible),
+            collection,
+            uses: None,
+            collection_details: None,
+            programmable_config: None,
+        }
+    }
+
+    // the account data as it sits on chain, zero padded to the full allocation
+    fn account_data(metadata: &Metadata) -> Vec<u8> {
+        let mut data = metadata.try_to_vec().unwrap();
+        data.resize(MAX_METADATA_LEN, 0);
+        data
+    }
+
+    fn verified(key: Pubkey) -> Option<Collection> {
+        Some(Collection { verified: true, key })
+    }
+
+    #[test]
+    fn reads_a_verified_collection() {
+        let mint = Pubkey::new_unique();
+        let collection = Pubkey::new_unique();
+        let data = account_data(&nft(mint, verified(collection)));
+
+        assert_eq!(
+            read_metadata(&data),
+            Some(MetadataFields { mint, token_standard: Some(0), collection: Some(collection) })
+        );
+    }
+
+    #[test]
+    fn unverified_collection_reads_as_none() {
+        let mint = Pubkey::new_unique();
+        let unverified = Some(Collection { verified: false, key: Pubkey::new_unique() });
+        let data = account_data(&nft(mint, unverified));
+
+        assert_eq!(read_metadata(&data).unwrap().collection, None);
+    }
+
+    #[test]
+    fn reads_metadata_without_creators_or_optional_fields() {
+        let mint = Pubkey::new_unique();
+        let mut metadata = nft(mint, None);
+        metadata.creators = None;
+        metadata.edition_nonce = None;
+        metadata.token_standard = None;
+
+        assert_eq!(
+            read_metadata(&account_data(&metadata)),
+            Some(MetadataFields { mint, token_standard: None, collection: None })
+        );
+    }
+
+    #[test]
+    fn fields_added_after_older_layouts_read_as_none() {
+        // accounts made before edition nonce, token standard and collection existed
+        // end after is_mutable and are zero padded from there
+        let mint = Pubkey::new_unique();
+        let mut metadata = nft(mint, None);
+        metadata.edition_nonce = None;
+        metadata.token_standard = None;
+        let mut data = metadata.try_to_vec().unwrap();
+        data.truncate(data.len() - 6);
+        data.resize(MAX_METADATA_LEN, 0);
+
+        assert_eq!(
+            read_metadata(&data),
+            Some(MetadataFields { mint, token_standard: None, collection: None })
+        );
+    }
+
+    #[test]
+    fn reads_the_token_standard_of_a_programmable_nft() {
+        let mint = Pubkey::new_unique();
+        let collection = Pubkey::new_unique();
+        let mut metadata = nft(mint, verified(collection));
+        metadata.token_standard = Some(TokenStandard::ProgrammableNonFungible);
+        metadata.programmable_config = Some(ProgrammableConfig::V1 { rule_set: None });
+
+        let fields = read_metadata(&account_data(&metadata)).unwrap();
+        assert_eq!(fields.token_standard, Some(PROGRAMMABLE_NON_FUNGIBLE));
+        assert_eq!(fields.collection, Some(collection));
+    }
+
+    #[test]
+    fn rejects_other_accounts_and_truncated_data() {
+        let data = account_data(&nft(Pubkey::new_unique(), None));
+
+        let mut edition = data.clone();
+        edition[0] = Key::MasterEditionV2 as u8;
+        assert_eq!(read_metadata(&edition), None);
+
+        // cut inside the uri
+        assert_eq!(read_metadata(&data[..1 + 32 + 32 + 4 + MAX_NAME_LENGTH + 100]), None);
+        assert_eq!(read_metadata(&[]), None);
+    }
+
+    fn check(metadata: &Metadata, collection: &Pubkey) -> Result<()> {
+        let (address, _) = Pubkey::find_program_address(
+            &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), metadata.mint.as_ref()],
+            &TOKEN_METADATA_PROGRAM_ID,
+        );
+        let mut lamports = 0;
+        let mut data = account_data(metadata);
+        let account = AccountInfo::new(
+            &address,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &TOKEN_METADATA_PROGRAM_ID,
+            false,
+            0,
+        );
+        check_collection(&account, &metadata.mint, collection)
+    }
+
+    #[test]
+    fn accepts_a_verified_member_of_the_collection() {
+        let collection = Pubkey::new_unique();
+        let metadata = nft(Pubkey::new_unique(), verified(collection));
+
+        assert!(check(&metadata, &collection).is_ok());
+        assert_eq!(
+            check(&metadata, &Pubkey::new_unique()).unwrap_err(),
+            EscrowError::CollectionMismatch.into()
+        );
+    }
+
+    #[test]
+    fn rejects_programmable_nfts() {
+        let collection = Pubkey::new_unique();
+        for token_standard in [
+            TokenStandard::ProgrammableNonFungible,
+            TokenStandard::ProgrammableNonFungibleEdition,
+        ] {
+            let mut metadata = nft(Pubkey::new_unique(), verified(collection));
+            metadata.token_standard = Some(token_standard);
+            metadata.programmable_config = Some(ProgrammableConfig::V1 { rule_set: None });
+
+            assert_eq!(
+                check(&metadata, &collection).unwrap_err(),
+                EscrowError::ProgrammableNftNotSupported.into()
+            );
+        }
+    }
+}
@@ -1,5 +1,17 @@
 // here in mod.rs, we are exporting all the context files through mod.rs so that we only need to imprt mod.rs and we have all the contexts files available
 
+pub mod errors;
+pub use errors::*;
+
+pub mod validate;
+pub use validate::*;
+
+pub mod metadata;
+pub use metadata::*;
+
+pub mod remaining_accounts;
+pub use remaining_accounts::*;
+
 pub mod make;
 pub use make::*;
 
@@ -8,3 +20,21 @@ pub use refund::*;
 
 pub mod take;
 pub use take::*;
+
+pub mod settle;
+pub use settle::*;
+
+pub mod open_basket;
+pub use open_basket::*;
+
+pub mod add_offered_item;
+pub use add_offered_item::*;
+
+pub mod seal_basket;
+pub use seal_basket::*;
+
+pub mod begin_take;
+pub use begin_take::*;
+
+pub mod take_chunk;
+pub use take_chunk::*;
@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+use crate::{Escrow, EscrowStatus, ExpectedMode, TokenData};
+use crate::contexts::{
+    errors::EscrowError,
+    validate::{check_basket, check_divisible},
+};
+
+#[derive(Accounts)]
+#[instruction(seed: u64, expected: Vec<TokenData>)]
+pub struct OpenBasket<'info> {
+
+    // the person who is creating the escrow -> maker
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    // new escrow account, sized for no offered items yet, add_offered_item grows it
+    #[account(
+        init,
+        payer = maker,
+        space = Escrow::space(0, expected.len()),
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    // system program
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> OpenBasket<'info> {
+
+    // start a staged basket, offered items are deposited one per add_offered_item
+    pub fn open(
+        &mut self,
+        seed: u64,
+        expected: Vec<TokenData>,
+        expected_mode: ExpectedMode,
+        units: u64,
+        bump: u8,
+    ) -> Result<()> {
+        check_basket(&expected)?;
+        require!(units > 0, EscrowError::InvalidUnits);
+        check_divisible(&expected, units)?;
+
+        self.escrow.set_inner(Escrow {
+            seed,                                      // seed for PDA
+            maker: self.maker.key(),                   // pubkey of escrow creator
+            offered: Vec::new(),                       // filled by add_offered_item
+            offered_remaining: Vec::new(),             // filled by add_offered_item
+            expected_remaining: expected.iter().map(|token| token.amount).collect(),
+            expected,                                  // list of tokens expected
+            expected_mode,                             // pay all expected tokens or one of them
+            total_units: units,                        // units the basket can be filled in
+            remaining_units: units,                    // nothing taken yet
+            status: EscrowStatus::Building,            // no take until the maker seals it
+            settled: 0,                                // nothing settled yet
+            bump,                                      // bump for escrow PDA
+        });
+        Ok(())
+    }
+}
@@ -1,13 +1,11 @@
 use anchor_lang::prelude::*;
 
-use anchor_spl::{
-    associated_token::AssociatedToken,
-    token_interface::{
-        close_account, transfer_checked, TokenInterface, TransferChecked, CloseAccount,
-    },
-};
+use anchor_spl::{associated_token::AssociatedToken, token_interface::TokenInterface};
 
-use crate::{Escrow};
+use crate::{Escrow, EscrowStatus};
+use crate::contexts::errors::EscrowError;
+use crate::contexts::remaining_accounts::RemainingAccounts;
+use crate::contexts::settle::{close_vault, release_item};
 
 #[derive(Accounts)]
 pub struct Refund<'info> {
@@ -16,10 +14,9 @@ pub struct Refund<'info> {
     #[account(mut)]
     pub maker: Signer<'info>,
 
-    // escrow account being closed
+    // escrow account, closed once every offered item is back with the maker
     #[account(
         mut,
-        close = maker,
         has_one = maker,
         seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
         bump = escrow.bump
@@ -37,8 +34,30 @@ pub struct Refund<'info> {
 }
 
 impl<'info> Refund<'info> {
-    pub fn refund_and_close_all(&self, remaining: &[AccountInfo<'info>]) -> Result<()> {
+    // refunds the offered items whose accounts are passed, picking up where the last chunk stopped,
+    // a basket small enough for one transaction is refunded and closed in a single call
+    pub fn refund_and_close_all(&mut self, remaining: &[AccountInfo<'info>]) -> Result<()> {
+        match self.escrow.status {
+            EscrowStatus::Building | EscrowStatus::Sealed => self.start_refund(),
+            EscrowStatus::Locked { expires_at, .. } => {
+                // a stalled staged take can be broken, but only before its first chunk,
+                // once the taker has paid anything the maker cannot also take the offered items back
+                require!(
+                    Clock::get()?.unix_timestamp >= expires_at,
+                    EscrowError::LockNotExpired
+                );
+                require!(self.escrow.settled == 0, EscrowError::TakeInProgress);
+                self.start_refund();
+            }
+            EscrowStatus::Refunding => {}
+        }
+
+        let maker = self.maker.to_account_info();
+        let escrow = self.escrow.to_account_info();
         let maker_key = self.maker.key();
+        let escrow_key = self.escrow.key();
+        let token_program = self.token_program.to_account_info();
+        let token_program_key = self.token_program.key();
 
         let signer_seeds: [&[&[u8]]; 1] = [&[
             b"escrow",
@@ -47,54 +66,32 @@ impl<'info> Refund<'info> {
             &[self.escrow.bump],
         ]];
 
-        let mut cursor = 0;
-
-        for token in &self.escrow.offered {
-            let mint = &remaining[cursor];
-            cursor += 1;
-
-            let vault = &remaining[cursor];
-            cursor += 1;
-
-            let maker_ata = &remaining[cursor];
-            cursor += 1;
-
-            // load mint to get decimals
-            let mint_data = anchor_spl::token::Mint::try_deserialize(&mut &mint.data.borrow()[..])?;
-            let decimals = mint_data.decimals;
-
-            // refund transfer from vault → maker
-            let transfer_accounts = TransferChecked {
-                from: vault.clone(),
-                mint: mint.clone(),
-                to: maker_ata.clone(),
-                authority: self.escrow.to_account_info(),
-            };
-
-            let transfer_ctx = CpiContext::new_with_signer(
-                self.token_program.to_account_info(),
-                transfer_accounts,
-                &signer_seeds,
-            );
-
-            transfer_checked(transfer_ctx, token.amount, decimals)?;
-
-            // close vault
-            let close_accounts = CloseAccount {
-                account: vault.clone(),
-                destination: self.maker.to_account_info(),
-                authority: self.escrow.to_account_info(),
-            };
+        let start = self.escrow.settled as usize;
+        let (mut accounts, count) =
+            RemainingAccounts::chunk(remaining, &self.escrow.offered[start..])?;
+
+        for index in start..start + count {
+            // the vault must be the escrow's own and the refund must go to the maker,
+            // the whole vault goes back so it can be closed whatever was sent to it
+            let token = &self.escrow.offered[index];
+            let item = accounts.release(token, &escrow_key, &maker_key, &token_program_key)?;
+            release_item(&token_program, &item, &escrow, item.balance, &signer_seeds)?;
+            close_vault(&token_program, item.from, &escrow, &maker, &signer_seeds)?;
+        }
 
-            let close_ctx = CpiContext::new_with_signer(
-                self.token_program.to_account_info(),
-                close_accounts,
-                &signer_seeds,
-            );
+        self.escrow.settled += count as u16;
 
-            close_account(close_ctx)?;
+        // every vault is closed, the escrow goes too
+        if self.escrow.settled as usize == self.escrow.offered.len() {
+            self.escrow.close(maker)?;
         }
 
         Ok(())
     }
+
+    // from here on the escrow can only be refunded, the cursor now counts refunded offered items
+    fn start_refund(&mut self) {
+        self.escrow.status = EscrowStatus::Refunding;
+        self.escrow.settled = 0;
+    }
 }
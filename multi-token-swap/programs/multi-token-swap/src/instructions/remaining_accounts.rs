@@ -0,0 +1,246 @@
+// typed parser for remaining_accounts: every basket item takes three accounts,
+// mint, source and destination, plus the NFT's metadata for a collection item,
+// each one is checked as it is read so a wrong account list fails with an escrow error
+// instead of an index panic
+
+use anchor_lang::prelude::*;
+
+use crate::{ItemKind, TokenData};
+use crate::contexts::errors::EscrowError;
+use crate::contexts::metadata::check_collection;
+use crate::contexts::validate::{check_ata, check_mint, check_vault, vault_address};
+
+// mint, source and destination for one basket item
+pub const ACCOUNTS_PER_ITEM: usize = 3;
+
+// accounts the parser reads for one item
+pub fn item_accounts_len(token: &TokenData) -> usize {
+    match token.kind {
+        ItemKind::Collection => ACCOUNTS_PER_ITEM + 1,
+        ItemKind::Token | ItemKind::Nft => ACCOUNTS_PER_ITEM,
+    }
+}
+
+// the checked accounts for moving one basket item
+pub struct ItemAccounts<'a, 'info> {
+    pub mint: &'a AccountInfo<'info>,
+    pub from: &'a AccountInfo<'info>,
+    pub to: &'a AccountInfo<'info>,
+    pub decimals: u8,
+    pub balance: u64,             // balance of `from` when it was checked
+}
+
+pub struct RemainingAccounts<'a, 'info> {
+    accounts: std::slice::Iter<'a, AccountInfo<'info>>,
+}
+
+impl<'a, 'info> RemainingAccounts<'a, 'info> {
+
+    // the list must hold exactly the accounts for every one of `items`
+    pub fn new<'t>(
+        accounts: &'a [AccountInfo<'info>],
+        items: impl IntoIterator<Item = &'t TokenData>,
+    ) -> Result<Self> {
+        let needed: usize = items.into_iter().map(item_accounts_len).sum();
+        require_eq!(accounts.len(), needed, EscrowError::AccountCountMismatch);
+        Ok(Self { accounts: accounts.iter() })
+    }
+
+    // staged takes and refunds settle as many of the `items_left` as the accounts cover,
+    // returns the parser and that item count, an empty list is only accepted when nothing is left
+    pub fn chunk<'t>(
+        accounts: &'a [AccountInfo<'info>],
+        items_left: impl IntoIterator<Item = &'t TokenData>,
+    ) -> Result<(Self, usize)> {
+        let mut items_left = items_left.into_iter();
+        let mut used = 0;
+        let mut count = 0;
+
+        while used < accounts.len() {
+            let Some(token) = items_left.next() else {
+                break;
+            };
+            used += item_accounts_len(token);
+            count += 1;
+        }
+
+        // the list has to end on an item boundary
+        require!(
+            used == accounts.len() && (count > 0 || items_left.next().is_none()),
+            EscrowError::AccountCountMismatch
+        );
+        Ok((Self { accounts: accounts.iter() }, count))
+    }
+
+    // make: maker's ATA -> the escrow's vault, which make creates so only its address is checked
+    pub fn deposit(
+        &mut self,
+        token: &TokenData,
+        maker: &AccountInfo<'info>,
+        escrow: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Result<ItemAccounts<'a, 'info>> {
+        require!(maker.is_signer, EscrowError::MissingSigner);
+
+        let (mint, decimals) = self.next_mint(token, token_program)?;
+
+        let from = self.next_writable()?;
+        let balance = check_ata(from, mint.key, maker.key, token_program)?;
+
+        let to = self.next_writable()?;
+        require_keys_eq!(
+            to.key(),
+            vault_address(escrow, mint.key, token_program),
+            EscrowError::VaultMismatch
+        );
+
+        Ok(ItemAccounts { mint, from, to, decimals, balance })
+    }
+
+    // take: taker's ATA -> maker's ATA,
+    // for a collection item the mint is the NFT the taker pays with and its metadata follows
+    pub fn payment(
+        &mut self,
+        token: &TokenData,
+        payer: &AccountInfo<'info>,
+        recipient: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Result<ItemAccounts<'a, 'info>> {
+        require!(payer.is_signer, EscrowError::MissingSigner);
+
+        let (mint, decimals) = self.next_mint(token, token_program)?;
+
+        let from = self.next_writable()?;
+        let balance = check_ata(from, mint.key, payer.key, token_program)?;
+
+        let to = self.next_writable()?;
+        check_ata(to, mint.key, recipient, token_program)?;
+
+        if token.kind == ItemKind::Collection {
+            let metadata = self.next()?;
+            check_collection(metadata, mint.key, &token.mint)?;
+        }
+
+        Ok(ItemAccounts { mint, from, to, decimals, balance })
+    }
+
+    // take and refund: the escrow's vault -> recipient's ATA
+    pub fn release(
+        &mut self,
+        token: &TokenData,
+        escrow: &Pubkey,
+        recipient: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Result<ItemAccounts<'a, 'info>> {
+        let (mint, decimals) = self.next_mint(token, token_program)?;
+
+        let from = self.next_writable()?;
+        let balance = check_vault(from, mint.key, escrow, token_program)?;
+
+        let to = self.next_writable()?;
+        check_ata(to, mint.key, recipient, token_program)?;
+
+        Ok(ItemAccounts { mint, from, to, decimals, balance })
+    }
+
+    fn next(&mut self) -> Result<&'a AccountInfo<'info>> {
+        self.accounts
+            .next()
+            .ok_or(error!(EscrowError::AccountCountMismatch))
+    }
+
+    fn next_writable(&mut self) -> Result<&'a AccountInfo<'info>> {
+        let account = self.next()?;
+        require!(account.is_writable, EscrowError::AccountNotWritable);
+        Ok(account)
+    }
+
+    fn next_mint(
+        &mut self,
+        token: &TokenData,
+        token_program: &Pubkey,
+    ) -> Result<(&'a AccountInfo<'info>, u8)> {
+        let mint = self.next()?;
+        let decimals = check_mint(mint, token, token_program)?;
+        Ok((mint, decimals))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(kind: ItemKind) -> TokenData {
+        TokenData { mint: Pubkey::new_unique(), amount: 1, kind }
+    }
+
+    // `len` blank accounts, the counts only look at the length of the list
+    fn accounts(len: usize) -> Vec<AccountInfo<'static>> {
+        (0..len)
+            .map(|_| {
+                AccountInfo::new(
+                    Box::leak(Box::new(Pubkey::new_unique())),
+                    false,
+                    true,
+                    Box::leak(Box::new(0)),
+                    &mut [],
+                    &crate::ID,
+                    false,
+                    0,
+                )
+            })
+            .collect()
+    }
+
+    fn new(len: usize, items: &[TokenData]) -> Result<()> {
+        RemainingAccounts::new(&accounts(len), items).map(|_| ())
+    }
+
+    fn chunk(len: usize, items_left: &[TokenData]) -> Result<usize> {
+        RemainingAccounts::chunk(&accounts(len), items_left).map(|(_, count)| count)
+    }
+
+    #[test]
+    fn new_needs_exactly_the_accounts_of_every_item() {
+        // a collection item carries the NFT's metadata on top of its three accounts
+        let items = [item(ItemKind::Token), item(ItemKind::Collection)];
+
+        assert!(new(7, &items).is_ok());
+        assert_eq!(new(6, &items).unwrap_err(), EscrowError::AccountCountMismatch.into());
+        assert_eq!(new(8, &items).unwrap_err(), EscrowError::AccountCountMismatch.into());
+        assert_eq!(new(0, &items).unwrap_err(), EscrowError::AccountCountMismatch.into());
+    }
+
+    #[test]
+    fn chunk_counts_the_items_its_accounts_cover() {
+        let items = [
+            item(ItemKind::Token),
+            item(ItemKind::Collection),
+            item(ItemKind::Nft),
+        ];
+
+        assert_eq!(chunk(3, &items).unwrap(), 1);
+        assert_eq!(chunk(7, &items).unwrap(), 2);
+        assert_eq!(chunk(10, &items).unwrap(), 3);
+    }
+
+    #[test]
+    fn chunk_must_end_on_an_item_boundary() {
+        let items = [item(ItemKind::Token), item(ItemKind::Collection)];
+
+        // partway through the first item, then partway through the collection item
+        assert_eq!(chunk(2, &items).unwrap_err(), EscrowError::AccountCountMismatch.into());
+        assert_eq!(chunk(5, &items).unwrap_err(), EscrowError::AccountCountMismatch.into());
+        // past the last item
+        assert_eq!(chunk(8, &items).unwrap_err(), EscrowError::AccountCountMismatch.into());
+    }
+
+    #[test]
+    fn chunk_takes_an_empty_list_only_when_nothing_is_left() {
+        assert_eq!(chunk(0, &[]).unwrap(), 0);
+        assert_eq!(
+            chunk(0, &[item(ItemKind::Token)]).unwrap_err(),
+            EscrowError::AccountCountMismatch.into()
+        );
+    }
+}
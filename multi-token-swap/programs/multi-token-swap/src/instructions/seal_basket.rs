@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::{Escrow, EscrowStatus};
+use crate::contexts::errors::EscrowError;
+
+#[derive(Accounts)]
+pub struct SealBasket<'info> {
+
+    // maker's pubkey
+    pub maker: Signer<'info>,
+
+    // escrow account being sealed
+    #[account(
+        mut,
+        has_one = maker,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+}
+
+impl<'info> SealBasket<'info> {
+
+    // stop adding items and open the basket for takers
+    pub fn seal(&mut self) -> Result<()> {
+        require!(
+            self.escrow.status == EscrowStatus::Building,
+            EscrowError::InvalidEscrowStatus
+        );
+        require!(!self.escrow.offered.is_empty(), EscrowError::EmptyBasket);
+
+        self.escrow.status = EscrowStatus::Sealed;
+        Ok(())
+    }
+}
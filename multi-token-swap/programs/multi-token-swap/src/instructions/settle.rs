@@ -0,0 +1,72 @@
+// token movements shared by take, the staged take and refund, one basket item at a time
+
+use anchor_lang::prelude::*;
+
+use anchor_spl::token_interface::{close_account, transfer_checked, CloseAccount, TransferChecked};
+
+use crate::contexts::remaining_accounts::ItemAccounts;
+
+// moves `amount` of one item out of a token account owned by the signing `authority`
+pub fn pay_item<'info>(
+    token_program: &AccountInfo<'info>,
+    item: &ItemAccounts<'_, 'info>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let transfer_accounts = TransferChecked {
+        from: item.from.clone(),
+        mint: item.mint.clone(),
+        to: item.to.clone(),
+        authority: authority.clone(),
+    };
+
+    let ctx = CpiContext::new(token_program.clone(), transfer_accounts);
+    transfer_checked(ctx, amount, item.decimals)
+}
+
+// moves `amount` of one offered item out of its vault, the escrow PDA signs
+pub fn release_item<'info>(
+    token_program: &AccountInfo<'info>,
+    item: &ItemAccounts<'_, 'info>,
+    escrow: &AccountInfo<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let transfer_accounts = TransferChecked {
+        from: item.from.clone(),
+        mint: item.mint.clone(),
+        to: item.to.clone(),
+        authority: escrow.clone(),
+    };
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        token_program.clone(),
+        transfer_accounts,
+        signer_seeds,
+    );
+
+    transfer_checked(transfer_ctx, amount, item.decimals)
+}
+
+// closes an emptied vault with its rent going to `rent_to`, the escrow PDA signs
+pub fn close_vault<'info>(
+    token_program: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    escrow: &AccountInfo<'info>,
+    rent_to: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let close_accounts = CloseAccount {
+        account: vault.clone(),
+        destination: rent_to.clone(),
+        authority: escrow.clone(),
+    };
+
+    let close_ctx = CpiContext::new_with_signer(
+        token_program.clone(),
+        close_accounts,
+        signer_seeds,
+    );
+
+    close_account(close_ctx)
+}
@@ -1,14 +1,11 @@
 use anchor_lang::prelude::*;
 
-use anchor_spl::{
-    associated_token::AssociatedToken,
-    token_interface::{
-        close_account, transfer_checked, TokenInterface,
-        TransferChecked, CloseAccount
-    },
-};
+use anchor_spl::{associated_token::AssociatedToken, token_interface::TokenInterface};
 
-use crate::{Escrow};
+use crate::{Escrow, EscrowStatus};
+use crate::contexts::errors::EscrowError;
+use crate::contexts::remaining_accounts::RemainingAccounts;
+use crate::contexts::settle::{close_vault, pay_item, release_item};
 
 #[derive(Accounts)]
 pub struct Take<'info> {
@@ -16,10 +13,9 @@ pub struct Take<'info> {
     #[account(mut)]
     pub taker: Signer<'info>,
 
-    // the escrow account being closed
+    // the escrow account, closed to the taker by the fill that takes the last units
     #[account(
         mut,
-        close = taker,
         has_one = maker,
         seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
         bump = escrow.bump
@@ -38,36 +34,48 @@ pub struct Take<'info> {
 }
 
 impl<'info> Take<'info> {
-    pub fn execute(&self, remaining: &[AccountInfo<'info>]) -> Result<()> {
-        let _escrow_key = self.escrow.key();
+    // fills `units` of the basket: the taker pays that share of every expected item,
+    // or of the `payment` alternative they picked, and receives that share of every offered item
+    pub fn execute(
+        &mut self,
+        units: u64,
+        payment: Option<u16>,
+        remaining: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        // a single transaction take needs a sealed basket or an abandoned staged take,
+        // staged ones go through begin_take / take_chunk
+        require!(
+            self.escrow.is_open_for_take(Clock::get()?.unix_timestamp),
+            EscrowError::InvalidEscrowStatus
+        );
+        self.escrow.check_units(units)?;
+        let is_final = units == self.escrow.remaining_units;
+        let paid = self.escrow.payment_items(payment)?;
+
+        let escrow = self.escrow.to_account_info();
+        let escrow_key = self.escrow.key();
         let maker_key = self.maker.key();
-
-        let mut account_cursor = 0;
+        let taker = self.taker.to_account_info();
+        let token_program = self.token_program.to_account_info();
+        let token_program_key = self.token_program.key();
+
+        // accounts for every paid expected token, then for every offered token
+        let mut accounts = RemainingAccounts::new(
+            remaining,
+            paid.iter()
+                .map(|&index| &self.escrow.expected[index])
+                .chain(&self.escrow.offered),
+        )?;
 
         // Transfer expected tokens from taker → maker
-        for token in &self.escrow.expected {
-            let mint = &remaining[account_cursor];
-            account_cursor += 1;
-
-            let taker_ata = &remaining[account_cursor];
-            account_cursor += 1;
-
-            let maker_ata = &remaining[account_cursor];
-            account_cursor += 1;
-
-            let mint_data = anchor_spl::token::Mint::try_deserialize(&mut &mint.data.borrow()[..])?;
-            let decimals = mint_data.decimals;
-
-            let transfer_accounts = TransferChecked {
-                from: taker_ata.clone(),
-                mint: mint.clone(),
-                to: maker_ata.clone(),
-                authority: self.taker.to_account_info(),
-            };
-
-            let ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
-            transfer_checked(ctx, token.amount, decimals)?;
+        for &index in &paid {
+            // the taker pays from their own account into one the maker owns
+            let amount = self.escrow.expected_fill(index, units);
+            let token = &self.escrow.expected[index];
+            let item = accounts.payment(token, &taker, &maker_key, &token_program_key)?;
+            pay_item(&token_program, &item, &taker, amount)?;
         }
+        self.escrow.record_expected_fill(units);
 
         // Seeds for vault PDA authority
         let signer_seeds: [&[&[u8]]; 1] = [&[
@@ -77,50 +85,37 @@ impl<'info> Take<'info> {
             &[self.escrow.bump],
         ]];
 
-        // Transfer offered tokens from vault → taker, then close vault
-        for token in &self.escrow.offered {
-            let mint = &remaining[account_cursor];
-            account_cursor += 1;
-
-            let vault = &remaining[account_cursor];
-            account_cursor += 1;
-
-            let taker_ata = &remaining[account_cursor];
-            account_cursor += 1;
-
-            let mint_data = anchor_spl::token::Mint::try_deserialize(&mut &mint.data.borrow()[..])?;
-            let decimals = mint_data.decimals;
-
-            // Transfer from vault to taker
-            let transfer_accounts = TransferChecked {
-                from: vault.clone(),
-                mint: mint.clone(),
-                to: taker_ata.clone(),
-                authority: self.escrow.to_account_info(),
-            };
-
-            let transfer_ctx = CpiContext::new_with_signer(
-                self.token_program.to_account_info(),
-                transfer_accounts,
-                &signer_seeds,
+        // Transfer offered tokens from vault → taker, the final fill also closes the vaults
+        for index in 0..self.escrow.offered.len() {
+            // the vault must be the escrow's own, the destination must belong to the taker
+            let amount = self.escrow.offered_fill(index, units);
+            let token = &self.escrow.offered[index];
+            let item = accounts.release(token, &escrow_key, taker.key, &token_program_key)?;
+
+            // the vault must still hold what the escrow recorded,
+            // the final fill sweeps anything sent to it on top so it can be closed
+            require!(
+                item.balance >= self.escrow.offered_remaining[index],
+                EscrowError::VaultBalanceMismatch
             );
+            let sent = if is_final { item.balance } else { amount };
 
-            transfer_checked(transfer_ctx, token.amount, decimals)?;
+            release_item(&token_program, &item, &escrow, sent, &signer_seeds)?;
+            self.escrow.offered_remaining[index] -= amount;
 
-            // Close the vault account
-            let close_accounts = CloseAccount {
-                account: vault.clone(),
-                destination: self.taker.to_account_info(),
-                authority: self.escrow.to_account_info(),
-            };
+            if is_final {
+                close_vault(&token_program, item.from, &escrow, &taker, &signer_seeds)?;
+            }
+        }
 
-            let close_ctx = CpiContext::new_with_signer(
-                self.token_program.to_account_info(),
-                close_accounts,
-                &signer_seeds,
-            );
+        self.escrow.remaining_units -= units;
 
-            close_account(close_ctx)?;
+        // the basket is fully taken, rent goes to the taker,
+        // otherwise it is open again, dropping any abandoned lock
+        if is_final {
+            self.escrow.close(taker)?;
+        } else {
+            self.escrow.status = EscrowStatus::Sealed;
         }
 
         Ok(())
@@ -0,0 +1,137 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{associated_token::AssociatedToken, token_interface::TokenInterface};
+
+use crate::{Escrow, EscrowStatus};
+use crate::contexts::errors::EscrowError;
+use crate::contexts::remaining_accounts::RemainingAccounts;
+use crate::contexts::settle::{close_vault, pay_item, release_item};
+
+#[derive(Accounts)]
+pub struct TakeChunk<'info> {
+    // whoever settles this chunk: the taker holding the lock pays with the first one,
+    // anyone can push the chunks after it
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    // the taker holding the lock, receives the offered items and the rent of what the final fill closes
+    /// CHECK: checked against the lock holder recorded on the escrow
+    #[account(mut)]
+    pub taker: UncheckedAccount<'info>,
+
+    // the escrow account, closed to the taker with the last chunk of the final fill
+    #[account(
+        mut,
+        has_one = maker,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    // the original escrow creator (maker)
+    /// CHECK: we're only transferring tokens to this address
+    #[account(mut)]
+    pub maker: UncheckedAccount<'info>,
+
+    // programs
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> TakeChunk<'info> {
+    // settles the next items of a locked basket: every paid expected item in the first chunk,
+    // then the offered ones, so the maker is paid in full before anything leaves the vaults
+    pub fn settle_chunk(&mut self, remaining: &[AccountInfo<'info>]) -> Result<()> {
+        let EscrowStatus::Locked { taker: lock_holder, units, payment, .. } = self.escrow.status
+        else {
+            return err!(EscrowError::InvalidEscrowStatus);
+        };
+        require_keys_eq!(lock_holder, self.taker.key(), EscrowError::NotLockHolder);
+
+        // an expired lock that has not been paid for is open to others,
+        // once paid the offered items are the lock holder's and anyone can push them through
+        self.escrow
+            .check_chunk_signer(self.signer.key, Clock::get()?.unix_timestamp)?;
+        let is_final = units == self.escrow.remaining_units;
+        let paid = self.escrow.payment_items(payment)?;
+
+        let escrow = self.escrow.to_account_info();
+        let escrow_key = self.escrow.key();
+        let maker_key = self.maker.key();
+        let signer = self.signer.to_account_info();
+        let taker = self.taker.to_account_info();
+        let token_program = self.token_program.to_account_info();
+        let token_program_key = self.token_program.key();
+
+        let total = paid.len() + self.escrow.offered.len();
+        let start = self.escrow.settled as usize;
+
+        // accounts for each item this chunk settles
+        let (mut accounts, count) = RemainingAccounts::chunk(
+            remaining,
+            paid.iter()
+                .map(|&index| &self.escrow.expected[index])
+                .chain(&self.escrow.offered)
+                .skip(start),
+        )?;
+        self.escrow.check_chunk_end(paid.len(), count)?;
+
+        // Seeds for vault PDA authority
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            maker_key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump],
+        ]];
+
+        for index in start..start + count {
+            if let Some(&expected) = paid.get(index) {
+                // Transfer an expected token's share from taker → maker, only the lock holder signs these
+                let amount = self.escrow.expected_fill(expected, units);
+                let token = &self.escrow.expected[expected];
+                let item = accounts.payment(token, &signer, &maker_key, &token_program_key)?;
+                pay_item(&token_program, &item, &signer, amount)?;
+            } else {
+                // Transfer an offered token's share from vault → taker, the final fill also closes the vault
+                let offered = index - paid.len();
+                let amount = self.escrow.offered_fill(offered, units);
+                let token = &self.escrow.offered[offered];
+                let item = accounts.release(token, &escrow_key, taker.key, &token_program_key)?;
+
+                // the vault must still hold what the escrow recorded,
+                // the final fill sweeps anything sent to it on top so it can be closed
+                require!(
+                    item.balance >= self.escrow.offered_remaining[offered],
+                    EscrowError::VaultBalanceMismatch
+                );
+                let sent = if is_final { item.balance } else { amount };
+
+                release_item(&token_program, &item, &escrow, sent, &signer_seeds)?;
+                self.escrow.offered_remaining[offered] -= amount;
+
+                if is_final {
+                    close_vault(&token_program, item.from, &escrow, &taker, &signer_seeds)?;
+                }
+            }
+        }
+
+        self.escrow.settled += count as u16;
+
+        if self.escrow.settled as usize == total {
+            self.escrow.record_expected_fill(units);
+            self.escrow.remaining_units -= units;
+
+            if is_final {
+                // the basket is fully taken, rent goes to the taker like a single transaction take
+                self.escrow.close(taker)?;
+            } else {
+                // the rest of the basket is open for the next taker
+                self.escrow.status = EscrowStatus::Sealed;
+                self.escrow.settled = 0;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+// checks for the accounts passed in remaining_accounts, anchor cannot validate them for us
+// so every mint, vault and token account is checked here before it is used in a cpi
+
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token_interface::{Mint, TokenAccount},
+};
+
+use crate::{ItemKind, TokenData};
+use crate::contexts::errors::EscrowError;
+
+// a basket list must not be empty, every amount must be non zero and every mint must appear once,
+// a repeated mint would point two items at the same vault, NFT and collection items are always one
+pub fn check_basket(items: &[TokenData]) -> Result<()> {
+    require!(!items.is_empty(), EscrowError::EmptyBasket);
+
+    for (index, item) in items.iter().enumerate() {
+        require!(item.amount > 0, EscrowError::ZeroAmount);
+        require!(
+            item.kind == ItemKind::Token || item.amount == 1,
+            EscrowError::InvalidNftAmount
+        );
+        require!(
+            !items[..index].iter().any(|other| other.mint == item.mint),
+            EscrowError::DuplicateMint
+        );
+    }
+
+    Ok(())
+}
+
+// offered items are deposited from a named mint, so a collection item cannot be offered
+pub fn check_offered(items: &[TokenData]) -> Result<()> {
+    check_basket(items)?;
+    require!(
+        items.iter().all(|item| item.kind != ItemKind::Collection),
+        EscrowError::CollectionNotOffered
+    );
+    Ok(())
+}
+
+// an NFT cannot be split, so a basket holding one can only be taken whole
+pub fn check_divisible(items: &[TokenData], units: u64) -> Result<()> {
+    require!(
+        units == 1 || items.iter().all(|item| item.kind == ItemKind::Token),
+        EscrowError::NftBasketNotDivisible
+    );
+    Ok(())
+}
+
+// the mint must be the one the item names, or any NFT for a collection item,
+// and belong to the token program in use, returns its decimals
+pub fn check_mint(mint: &AccountInfo, item: &TokenData, token_program: &Pubkey) -> Result<u8> {
+    if item.kind != ItemKind::Collection {
+        require_keys_eq!(mint.key(), item.mint, EscrowError::MintMismatch);
+    }
+    require_keys_eq!(*mint.owner, *token_program, EscrowError::InvalidMint);
+
+    let mint_data = Mint::try_deserialize(&mut &mint.data.borrow()[..])
+        .map_err(|_| error!(EscrowError::InvalidMint))?;
+    if item.kind != ItemKind::Token {
+        check_nft(&mint_data)?;
+    }
+    Ok(mint_data.decimals)
+}
+
+// an NFT mint has no decimals and exactly one token in existence
+pub fn check_nft(mint: &Mint) -> Result<()> {
+    require!(mint.decimals == 0 && mint.supply == 1, EscrowError::NotAnNft);
+    Ok(())
+}
+
+// the token account must belong to the token program in use, hold `mint` and be owned by `authority`,
+// returns its balance
+pub fn check_token_account(
+    account: &AccountInfo,
+    mint: &Pubkey,
+    authority: &Pubkey,
+    token_program: &Pubkey,
+) -> Result<u64> {
+    require_keys_eq!(*account.owner, *token_program, EscrowError::TokenProgramMismatch);
+
+    let token_account = TokenAccount::try_deserialize(&mut &account.data.borrow()[..])
+        .map_err(|_| error!(EscrowError::InvalidTokenAccount))?;
+    require_keys_eq!(token_account.mint, *mint, EscrowError::TokenAccountMintMismatch);
+    require_keys_eq!(token_account.owner, *authority, EscrowError::AuthorityMismatch);
+
+    Ok(token_account.amount)
+}
+
+// the token account must be `authority`'s ATA for `mint`, on top of the regular token account checks,
+// returns its balance
+pub fn check_ata(
+    account: &AccountInfo,
+    mint: &Pubkey,
+    authority: &Pubkey,
+    token_program: &Pubkey,
+) -> Result<u64> {
+    require_keys_eq!(
+        account.key(),
+        get_associated_token_address_with_program_id(authority, mint, token_program),
+        EscrowError::NotAssociatedTokenAccount
+    );
+    check_token_account(account, mint, authority, token_program)
+}
+
+// the vault must be the escrow's ATA for `mint`, on top of the regular token account checks,
+// returns its balance
+pub fn check_vault(
+    vault: &AccountInfo,
+    mint: &Pubkey,
+    escrow: &Pubkey,
+    token_program: &Pubkey,
+) -> Result<u64> {
+    require_keys_eq!(
+        vault.key(),
+        vault_address(escrow, mint, token_program),
+        EscrowError::VaultMismatch
+    );
+    check_token_account(vault, mint, escrow, token_program)
+}
+
+// balance of a token account the program already checked or created
+pub fn token_balance(account: &AccountInfo) -> Result<u64> {
+    let token_account = TokenAccount::try_deserialize(&mut &account.data.borrow()[..])
+        .map_err(|_| error!(EscrowError::InvalidTokenAccount))?;
+    Ok(token_account.amount)
+}
+
+// address of the vault holding `mint` for an escrow: the escrow PDA's ATA
+pub fn vault_address(escrow: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(escrow, mint, token_program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(amount: u64, kind: ItemKind) -> TokenData {
+        TokenData { mint: Pubkey::new_unique(), amount, kind }
+    }
+
+    #[test]
+    fn a_basket_list_cannot_be_empty() {
+        assert_eq!(check_basket(&[]).unwrap_err(), EscrowError::EmptyBasket.into());
+        assert_eq!(check_offered(&[]).unwrap_err(), EscrowError::EmptyBasket.into());
+    }
+
+    #[test]
+    fn basket_amounts_must_be_non_zero() {
+        let items = [item(10, ItemKind::Token), item(0, ItemKind::Token)];
+        assert_eq!(check_basket(&items).unwrap_err(), EscrowError::ZeroAmount.into());
+    }
+
+    #[test]
+    fn a_mint_appears_once_per_basket_list() {
+        let token = item(10, ItemKind::Token);
+        let items = [token.clone(), item(5, ItemKind::Token), token];
+        assert_eq!(check_basket(&items).unwrap_err(), EscrowError::DuplicateMint.into());
+    }
+
+    #[test]
+    fn nft_and_collection_items_are_always_one() {
+        assert!(check_basket(&[item(1, ItemKind::Nft), item(1, ItemKind::Collection)]).is_ok());
+        assert_eq!(
+            check_basket(&[item(2, ItemKind::Nft)]).unwrap_err(),
+            EscrowError::InvalidNftAmount.into()
+        );
+        assert_eq!(
+            check_basket(&[item(2, ItemKind::Collection)]).unwrap_err(),
+            EscrowError::InvalidNftAmount.into()
+        );
+    }
+
+    #[test]
+    fn collection_items_cannot_be_offered() {
+        let items = [item(10, ItemKind::Token), item(1, ItemKind::Collection)];
+
+        assert!(check_basket(&items).is_ok());
+        assert_eq!(
+            check_offered(&items).unwrap_err(),
+            EscrowError::CollectionNotOffered.into()
+        );
+        assert!(check_offered(&[item(10, ItemKind::Token), item(1, ItemKind::Nft)]).is_ok());
+    }
+
+    #[test]
+    fn a_basket_with_an_nft_is_taken_whole() {
+        let tokens = [item(10, ItemKind::Token), item(20, ItemKind::Token)];
+        let with_nft = [item(10, ItemKind::Token), item(1, ItemKind::Nft)];
+        let with_collection = [item(1, ItemKind::Collection)];
+
+        assert!(check_divisible(&tokens, 4).is_ok());
+        assert!(check_divisible(&with_nft, 1).is_ok());
+        assert_eq!(
+            check_divisible(&with_nft, 2).unwrap_err(),
+            EscrowError::NftBasketNotDivisible.into()
+        );
+        assert_eq!(
+            check_divisible(&with_collection, 2).unwrap_err(),
+            EscrowError::NftBasketNotDivisible.into()
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 
+#[path = "instructions/mod.rs"]
 pub mod contexts;
 use contexts::*;
 
@@ -17,28 +18,32 @@ pub mod multi_token_swap {
         seed: u64,
         offered: Vec<TokenData>,
         expected: Vec<TokenData>,
+        expected_mode: ExpectedMode,
+        units: u64,
     ) -> Result<()> {
-        let _escrow_key = ctx.accounts.escrow.key();
-        let remaining = &ctx.remaining_accounts;
+        let maker = ctx.accounts.maker.to_account_info();
+        let escrow_key = ctx.accounts.escrow.key();
+        let token_program_key = ctx.accounts.token_program.key();
 
-        let mut account_cursor = 0;
+        check_offered(&offered)?;
+        check_basket(&expected)?;
+        check_divisible(&offered, units)?;
+        check_divisible(&expected, units)?;
 
-        for token in &offered {
-            // mint
-            let mint_account = &remaining[account_cursor];
-            account_cursor += 1;
-
-            // maker ATA (source)
-            let from_account = &remaining[account_cursor];
-            account_cursor += 1;
+        // mint, maker ATA (source) and vault ATA (destination) for each offered token
+        let mut accounts = RemainingAccounts::new(ctx.remaining_accounts, &offered)?;
 
-            // vault ATA (destination)
-            let to_account = &remaining[account_cursor];
-            account_cursor += 1;
+        for token in &offered {
+            let ItemAccounts {
+                mint: mint_account,
+                from: from_account,
+                to: to_account,
+                decimals,
+                ..
+            } = accounts.deposit(token, &maker, &escrow_key, &token_program_key)?;
 
-            // Load mint to get decimals
-            let mint_data = anchor_spl::token::Mint::try_deserialize(&mut &mint_account.data.borrow()[..])?;
-            let decimals = mint_data.decimals;
+            // Create the vault owned by the escrow at the address the parser checked
+            ctx.accounts.create_vault(mint_account, to_account)?;
 
             ctx.accounts.deposit_single_token(
                 mint_account,
@@ -49,15 +54,56 @@ pub mod multi_token_swap {
             )?;
         }
 
-        ctx.accounts.save_escrow(seed, offered, expected, ctx.bumps.escrow)
+        ctx.accounts.save_escrow(
+            seed,
+            offered,
+            expected,
+            expected_mode,
+            units,
+            ctx.bumps.escrow,
+        )
     }
 
-    pub fn take<'info>(ctx: Context<'_, '_, '_, 'info, Take<'info>>) -> Result<()> {
-        ctx.accounts.execute(&ctx.remaining_accounts)
+    pub fn take<'info>(
+        ctx: Context<'_, '_, '_, 'info, Take<'info>>,
+        units: u64,
+        payment: Option<u16>,
+    ) -> Result<()> {
+        ctx.accounts.execute(units, payment, ctx.remaining_accounts)
     }
 
     pub fn refund<'info>(ctx: Context<'_, '_, '_, 'info, Refund<'info>>) -> Result<()> {
-        ctx.accounts.refund_and_close_all(&ctx.remaining_accounts)
+        ctx.accounts.refund_and_close_all(ctx.remaining_accounts)
+    }
+
+    pub fn open_basket(
+        ctx: Context<OpenBasket>,
+        seed: u64,
+        expected: Vec<TokenData>,
+        expected_mode: ExpectedMode,
+        units: u64,
+    ) -> Result<()> {
+        ctx.accounts.open(seed, expected, expected_mode, units, ctx.bumps.escrow)
+    }
+
+    pub fn add_offered_item(
+        ctx: Context<AddOfferedItem>,
+        amount: u64,
+        kind: ItemKind,
+    ) -> Result<()> {
+        ctx.accounts.add(amount, kind)
+    }
+
+    pub fn seal_basket(ctx: Context<SealBasket>) -> Result<()> {
+        ctx.accounts.seal()
+    }
+
+    pub fn begin_take(ctx: Context<BeginTake>, units: u64, payment: Option<u16>) -> Result<()> {
+        ctx.accounts.lock(units, payment)
+    }
+
+    pub fn take_chunk<'info>(ctx: Context<'_, '_, '_, 'info, TakeChunk<'info>>) -> Result<()> {
+        ctx.accounts.settle_chunk(ctx.remaining_accounts)
     }
 
 
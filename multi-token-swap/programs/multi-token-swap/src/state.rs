@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;       // Anchor basic types and macros
 
+use crate::contexts::errors::EscrowError;
+
 #[account]                         // This is the main escrow account
 #[derive(InitSpace)]               // Auto-calculates space needed for account
 pub struct Escrow {
@@ -9,11 +11,386 @@ pub struct Escrow {
     pub offered: Vec<TokenData>,  // Tokens deposited into vault
     #[max_len(10)]                // Max 10 tokens expected
     pub expected: Vec<TokenData>, // Tokens the maker wants in return
+    pub expected_mode: ExpectedMode, // Whether the taker pays every expected token or picks one
+    #[max_len(10)]                // One per offered token
+    pub offered_remaining: Vec<u64>,  // Offered amounts still in the vaults
+    #[max_len(10)]                // One per expected token
+    pub expected_remaining: Vec<u64>, // Expected amounts still owed by future takers
+    pub total_units: u64,         // Units the basket is split into, 1 means it can only be taken whole
+    pub remaining_units: u64,     // Units not yet taken
+    pub status: EscrowStatus,     // Where the escrow is in its lifecycle
+    pub settled: u16,             // Items already settled by a staged take or a chunked refund
     pub bump: u8,                 // PDA bump
 }
 
+// make fits at most this many items per list, staged baskets grow past it with realloc
+pub const MAX_ITEMS_PER_LIST: usize = 10;
+
+// how long a staged take holds the lock before the maker may break it with a refund
+pub const TAKE_LOCK_DURATION: i64 = 10 * 60;
+
+// space for one basket item: its TokenData and its remaining amount
+const ITEM_SPACE: usize = TokenData::INIT_SPACE + 8;
+
+impl Escrow {
+    // account space for an escrow holding `offered` and `expected` items
+    pub fn space(offered: usize, expected: usize) -> usize {
+        8 + Escrow::INIT_SPACE + (offered + expected) * ITEM_SPACE
+            - 2 * MAX_ITEMS_PER_LIST * ITEM_SPACE
+    }
+
+    // a take can start on a sealed basket, or take over a staged take whose lock expired
+    // before its first chunk settled
+    pub fn is_open_for_take(&self, now: i64) -> bool {
+        match self.status {
+            EscrowStatus::Sealed => true,
+            EscrowStatus::Locked { expires_at, .. } => now >= expires_at && self.settled == 0,
+            EscrowStatus::Building | EscrowStatus::Refunding => false,
+        }
+    }
+
+    // who may settle the next chunk of a staged take: the payment goes in with the first chunk,
+    // so until something settled only the lock holder may, and only while the lock lasts,
+    // after that anyone may push the offered items on to the lock holder
+    pub fn check_chunk_signer(&self, signer: &Pubkey, now: i64) -> Result<()> {
+        let EscrowStatus::Locked { taker, expires_at, .. } = self.status else {
+            return err!(EscrowError::InvalidEscrowStatus);
+        };
+        if self.settled > 0 {
+            return Ok(());
+        }
+
+        require_keys_eq!(*signer, taker, EscrowError::NotLockHolder);
+        require!(now < expires_at, EscrowError::LockExpired);
+        Ok(())
+    }
+
+    // a chunk of `count` items never ends partway through the `paid` expected items,
+    // so the taker cannot hold the lock on a partial payment
+    pub fn check_chunk_end(&self, paid: usize, count: usize) -> Result<()> {
+        require!(
+            self.settled as usize + count >= paid,
+            EscrowError::PaymentSplitAcrossChunks
+        );
+        Ok(())
+    }
+
+    // a fill takes at least one unit and no more than are left
+    pub fn check_units(&self, units: u64) -> Result<()> {
+        require!(
+            units > 0 && units <= self.remaining_units,
+            EscrowError::InvalidUnits
+        );
+        Ok(())
+    }
+
+    // indices of the expected items a taker pays: all of them, or the alternative they picked
+    pub fn payment_items(&self, payment: Option<u16>) -> Result<Vec<usize>> {
+        match (self.expected_mode, payment) {
+            (ExpectedMode::AllOf, None) => Ok((0..self.expected.len()).collect()),
+            (ExpectedMode::AnyOf, Some(index)) if (index as usize) < self.expected.len() => {
+                Ok(vec![index as usize])
+            }
+            _ => err!(EscrowError::InvalidPaymentChoice),
+        }
+    }
+
+    // after a fill of `units` every expected item is owed that much less, in any-of mode the
+    // alternatives that were not picked shrink too so each one still prices exactly the units left
+    pub fn record_expected_fill(&mut self, units: u64) {
+        for index in 0..self.expected.len() {
+            let amount = self.expected_fill(index, units);
+            self.expected_remaining[index] -= amount;
+        }
+    }
+
+    // what the taker pays of expected item `index` for `units`, rounded up for the maker
+    pub fn expected_fill(&self, index: usize, units: u64) -> u64 {
+        self.item_fill(
+            self.expected[index].amount,
+            self.expected_remaining[index],
+            units,
+            true,
+        )
+    }
+
+    // what the taker receives of offered item `index` for `units`, rounded down for the maker
+    pub fn offered_fill(&self, index: usize, units: u64) -> u64 {
+        self.item_fill(
+            self.offered[index].amount,
+            self.offered_remaining[index],
+            units,
+            false,
+        )
+    }
+
+    // an item's share of the basket for `units`, the fill taking the last units
+    // gets exactly what is left of the item so rounding never leaves dust behind
+    fn item_fill(&self, amount: u64, remaining: u64, units: u64, round_up: bool) -> u64 {
+        if units == self.remaining_units {
+            return remaining;
+        }
+
+        let scaled = amount as u128 * units as u128;
+        let total = self.total_units as u128;
+        let share = if round_up { scaled.div_ceil(total) } else { scaled / total };
+
+        // units never exceed total_units, so the share fits in the item amount
+        (share as u64).min(remaining)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]
+pub enum EscrowStatus {
+    Building,                     // staged basket still taking offered items
+    Sealed,                       // open for a take
+    Locked {                      // a staged take is settling in chunks
+        taker: Pubkey,            // only this taker can settle the next chunk
+        expires_at: i64,          // unix timestamp after which the maker can break the lock
+        units: u64,               // units of the basket this take fills
+        payment: Option<u16>,     // expected alternative picked by the taker in any-of mode
+    },
+    Refunding,                    // the maker is refunding, possibly in chunks
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]
+pub enum ExpectedMode {
+    AllOf,                        // the taker pays every expected token
+    AnyOf,                        // the expected tokens are alternatives, the taker pays one of them
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone)] // Custom token struct
 pub struct TokenData {
-    pub mint: Pubkey,             // Mint of the token
-    pub amount: u64,              // Amount of tokens
+    pub mint: Pubkey,             // Mint of the token, or of the collection for a collection item
+    pub amount: u64,              // Amount of tokens for the whole basket
+    pub kind: ItemKind,           // What kind of item this is
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]
+pub enum ItemKind {
+    Token,                        // any fungible token of this mint
+    Nft,                          // this exact NFT: 0 decimals, supply of 1
+    Collection,                   // expected only: any NFT that is a verified member of this collection
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(amount: u64) -> TokenData {
+        TokenData { mint: Pubkey::new_unique(), amount, kind: ItemKind::Token }
+    }
+
+    // one offered and one expected item, split into `units`
+    fn basket(offered: u64, expected: u64, units: u64) -> Escrow {
+        Escrow {
+            seed: 0,
+            maker: Pubkey::new_unique(),
+            offered: vec![item(offered)],
+            expected: vec![item(expected)],
+            expected_mode: ExpectedMode::AllOf,
+            offered_remaining: vec![offered],
+            expected_remaining: vec![expected],
+            total_units: units,
+            remaining_units: units,
+            status: EscrowStatus::Sealed,
+            settled: 0,
+            bump: 255,
+        }
+    }
+
+    // settles a fill the way take does, returns what the taker paid and received
+    fn fill(escrow: &mut Escrow, units: u64) -> (u64, u64) {
+        let paid = escrow.expected_fill(0, units);
+        let received = escrow.offered_fill(0, units);
+        escrow.record_expected_fill(units);
+        escrow.offered_remaining[0] -= received;
+        escrow.remaining_units -= units;
+        (paid, received)
+    }
+
+    #[test]
+    fn fills_are_proportional_to_units() {
+        let escrow = basket(1_000, 400, 4);
+
+        assert_eq!(escrow.offered_fill(0, 1), 250);
+        assert_eq!(escrow.expected_fill(0, 1), 100);
+        assert_eq!(escrow.offered_fill(0, 3), 750);
+        assert_eq!(escrow.expected_fill(0, 3), 300);
+    }
+
+    #[test]
+    fn rounding_favours_the_maker() {
+        let escrow = basket(100, 100, 3);
+
+        // the taker receives a third rounded down and pays a third rounded up
+        assert_eq!(escrow.offered_fill(0, 1), 33);
+        assert_eq!(escrow.expected_fill(0, 1), 34);
+        assert_eq!(escrow.offered_fill(0, 2), 66);
+        assert_eq!(escrow.expected_fill(0, 2), 67);
+    }
+
+    #[test]
+    fn final_fill_settles_exactly_what_is_left() {
+        let mut escrow = basket(100, 100, 3);
+
+        assert_eq!(fill(&mut escrow, 1), (34, 33));
+        assert_eq!(fill(&mut escrow, 1), (34, 33));
+        // the last unit takes the leftovers of both items, nothing stays behind
+        assert_eq!(fill(&mut escrow, 1), (32, 34));
+        assert_eq!(escrow.offered_remaining, vec![0]);
+        assert_eq!(escrow.expected_remaining, vec![0]);
+    }
+
+    #[test]
+    fn taking_every_unit_at_once_settles_the_whole_basket() {
+        let mut escrow = basket(100, 7, 3);
+        assert_eq!(fill(&mut escrow, 3), (7, 100));
+    }
+
+    #[test]
+    fn rounded_up_payments_are_capped_by_what_is_still_owed() {
+        // 1 base unit expected across 3 units, the first fill pays it in full
+        let mut escrow = basket(3, 1, 3);
+
+        assert_eq!(fill(&mut escrow, 1), (1, 1));
+        assert_eq!(fill(&mut escrow, 1), (0, 1));
+        assert_eq!(fill(&mut escrow, 1), (0, 1));
+    }
+
+    #[test]
+    fn large_amounts_do_not_overflow() {
+        let escrow = basket(u64::MAX, u64::MAX, u64::MAX);
+
+        assert_eq!(escrow.offered_fill(0, u64::MAX - 1), u64::MAX - 1);
+        assert_eq!(escrow.expected_fill(0, 1), 1);
+
+        let escrow = basket(u64::MAX, u64::MAX, 2);
+        assert_eq!(escrow.offered_fill(0, 1), u64::MAX / 2);
+        assert_eq!(escrow.expected_fill(0, 1), u64::MAX / 2 + 1);
+    }
+
+    // one offered item against the `expected` amounts in `mode`, split into `units`
+    fn alternatives(mode: ExpectedMode, expected: &[u64], units: u64) -> Escrow {
+        let mut escrow = basket(100, 0, units);
+        escrow.expected = expected.iter().map(|&amount| item(amount)).collect();
+        escrow.expected_remaining = expected.to_vec();
+        escrow.expected_mode = mode;
+        escrow
+    }
+
+    #[test]
+    fn all_of_pays_every_expected_item() {
+        let escrow = alternatives(ExpectedMode::AllOf, &[10, 20, 30], 1);
+
+        assert_eq!(escrow.payment_items(None).unwrap(), vec![0, 1, 2]);
+        assert_eq!(
+            escrow.payment_items(Some(0)).unwrap_err(),
+            EscrowError::InvalidPaymentChoice.into()
+        );
+    }
+
+    #[test]
+    fn any_of_pays_the_picked_alternative() {
+        let escrow = alternatives(ExpectedMode::AnyOf, &[10, 20, 30], 1);
+
+        assert_eq!(escrow.payment_items(Some(1)).unwrap(), vec![1]);
+        assert_eq!(escrow.payment_items(Some(2)).unwrap(), vec![2]);
+        assert_eq!(
+            escrow.payment_items(None).unwrap_err(),
+            EscrowError::InvalidPaymentChoice.into()
+        );
+        assert_eq!(
+            escrow.payment_items(Some(3)).unwrap_err(),
+            EscrowError::InvalidPaymentChoice.into()
+        );
+    }
+
+    #[test]
+    fn a_fill_shrinks_the_alternatives_that_were_not_picked() {
+        let mut escrow = alternatives(ExpectedMode::AnyOf, &[100, 40], 4);
+
+        // one unit paid with the first alternative, the second one now prices the three left too
+        escrow.record_expected_fill(1);
+        escrow.remaining_units -= 1;
+        assert_eq!(escrow.expected_remaining, vec![75, 30]);
+
+        // the last units are paid with the second alternative at exactly what is left of it
+        assert_eq!(escrow.expected_fill(1, 3), 30);
+        escrow.record_expected_fill(3);
+        assert_eq!(escrow.expected_remaining, vec![0, 0]);
+    }
+
+    // a basket locked to `taker` until `expires_at`, with `settled` items already settled
+    fn locked(taker: Pubkey, expires_at: i64, settled: u16) -> Escrow {
+        let mut escrow = basket(100, 100, 1);
+        escrow.status = EscrowStatus::Locked { taker, expires_at, units: 1, payment: None };
+        escrow.settled = settled;
+        escrow
+    }
+
+    #[test]
+    fn an_unpaid_lock_is_settled_by_its_holder_until_it_expires() {
+        let holder = Pubkey::new_unique();
+        let escrow = locked(holder, 1_000, 0);
+
+        assert!(escrow.check_chunk_signer(&holder, 999).is_ok());
+        assert_eq!(
+            escrow.check_chunk_signer(&Pubkey::new_unique(), 999).unwrap_err(),
+            EscrowError::NotLockHolder.into()
+        );
+        assert_eq!(
+            escrow.check_chunk_signer(&holder, 1_000).unwrap_err(),
+            EscrowError::LockExpired.into()
+        );
+        assert!(!escrow.is_open_for_take(999));
+    }
+
+    #[test]
+    fn an_expired_unpaid_lock_can_be_taken_over() {
+        let escrow = locked(Pubkey::new_unique(), 1_000, 0);
+
+        assert!(escrow.is_open_for_take(1_000));
+        assert!(escrow.is_open_for_take(5_000));
+    }
+
+    #[test]
+    fn a_paid_lock_can_be_finished_by_anyone_and_not_taken_over() {
+        let holder = Pubkey::new_unique();
+        let escrow = locked(holder, 1_000, 1);
+
+        // before and after the lock expires, the payment is in so the basket is the holder's
+        assert!(escrow.check_chunk_signer(&holder, 999).is_ok());
+        assert!(escrow.check_chunk_signer(&Pubkey::new_unique(), 999).is_ok());
+        assert!(escrow.check_chunk_signer(&holder, 5_000).is_ok());
+        assert!(escrow.check_chunk_signer(&Pubkey::new_unique(), 5_000).is_ok());
+        assert!(!escrow.is_open_for_take(999));
+        assert!(!escrow.is_open_for_take(5_000));
+    }
+
+    #[test]
+    fn chunks_only_settle_a_locked_basket() {
+        let escrow = basket(100, 100, 1);
+        assert_eq!(
+            escrow.check_chunk_signer(&Pubkey::new_unique(), 0).unwrap_err(),
+            EscrowError::InvalidEscrowStatus.into()
+        );
+    }
+
+    #[test]
+    fn the_first_chunk_pays_in_full() {
+        let escrow = locked(Pubkey::new_unique(), 1_000, 0);
+
+        // three expected items to pay, a chunk stopping after two of them is rejected
+        assert_eq!(
+            escrow.check_chunk_end(3, 2).unwrap_err(),
+            EscrowError::PaymentSplitAcrossChunks.into()
+        );
+        assert!(escrow.check_chunk_end(3, 3).is_ok());
+        assert!(escrow.check_chunk_end(3, 5).is_ok());
+
+        // later chunks only carry offered items
+        let escrow = locked(Pubkey::new_unique(), 1_000, 3);
+        assert!(escrow.check_chunk_end(3, 1).is_ok());
+    }
 }
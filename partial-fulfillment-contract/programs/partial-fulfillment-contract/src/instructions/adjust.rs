@@ -0,0 +1,132 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::state::Escrow;
+use crate::instructions::EscrowError;
+
+#[derive(Accounts)]
+pub struct Adjust<'info> {
+    // Maker of the escrow
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    // Mint of token A (escrowed token)
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    // Maker's token A ATA (receives withdrawals, funds top-ups)
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    // Escrow account
+    #[account(
+        mut,
+        has_one = maker,
+        has_one = mint_a,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    // Vault account (holds token A)
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    // SPL program interfaces
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Adjust<'info> {
+    /// Moves part of the remaining token A back to the maker while the escrow stays open.
+    /// Withdrawing everything is what refund is for.
+    pub fn withdraw(&mut self, amount: u64) -> Result<()> {
+        require!(amount > 0, EscrowError::InvalidAmount);
+        require!(
+            amount < self.escrow.remaining_amount,
+            EscrowError::WithdrawWouldEmptyEscrow
+        );
+
+        // Build signer seeds for the escrow PDA
+        let maker_key = self.maker.key();
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            maker_key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump],
+        ]];
+
+        // 1. Transfer token A from vault to maker
+        let transfer = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.maker_ata_a.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            transfer,
+            &signer_seeds,
+        );
+        transfer_checked(ctx, amount, self.mint_a.decimals)?;
+
+        // 2. Shrink the order by the same amount
+        self.escrow.total_amount = self
+            .escrow
+            .total_amount
+            .checked_sub(amount)
+            .ok_or(EscrowError::Overflow)?;
+        self.escrow.remaining_amount = self
+            .escrow
+            .remaining_amount
+            .checked_sub(amount)
+            .ok_or(EscrowError::Overflow)?;
+
+        Ok(())
+    }
+
+    /// Deposits more token A into the vault, growing the order in place
+    pub fn top_up(&mut self, amount: u64) -> Result<()> {
+        require!(amount > 0, EscrowError::InvalidAmount);
+
+        // 1. Transfer token A from maker to vault
+        let transfer = TransferChecked {
+            from: self.maker_ata_a.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.maker.to_account_info(),
+        };
+
+        let ctx = CpiContext::new(self.token_program.to_account_info(), transfer);
+        transfer_checked(ctx, amount, self.mint_a.decimals)?;
+
+        // 2. Grow the order by the same amount
+        self.escrow.total_amount = self
+            .escrow
+            .total_amount
+            .checked_add(amount)
+            .ok_or(EscrowError::Overflow)?;
+        self.escrow.remaining_amount = self
+            .escrow
+            .remaining_amount
+            .checked_add(amount)
+            .ok_or(EscrowError::Overflow)?;
+
+        self.escrow.check_all_or_none_fits()
+    }
+}
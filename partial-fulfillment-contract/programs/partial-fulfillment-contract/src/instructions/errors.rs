@@ -8,4 +8,40 @@ pub enum EscrowError {
     ExceedsRemainingAmount,
     #[msg("Calculation overflow occurred")]
     Overflow,
+    #[msg("Price numerator and denominator must both be greater than zero")]
+    InvalidPrice,
+    #[msg("Minimum fill must not exceed maximum fill, and maximum fill must be greater than zero")]
+    InvalidFillLimits,
+    #[msg("Fill is below the escrow's minimum fill size")]
+    FillBelowMinimum,
+    #[msg("Fill is above the escrow's maximum fill size")]
+    FillAboveMaximum,
+    #[msg("Token A received is below the taker's minimum")]
+    SlippageExceeded,
+    #[msg("The escrow price changed since the taker's quote")]
+    PriceVersionMismatch,
+    #[msg("Withdrawing the whole remaining amount is done with refund")]
+    WithdrawWouldEmptyEscrow,
+    #[msg("Minimum fill percentage must be between 1 and 10000 basis points")]
+    InvalidOrderType,
+    #[msg("All-or-none order must be filled completely in a single take")]
+    AllOrNoneRequiresFullFill,
+    #[msg("Fill is below the escrow's minimum fill percentage")]
+    FillBelowMinimumPercentage,
+    #[msg("Expiry must be in the future")]
+    ExpiryInPast,
+    #[msg("The escrow has expired")]
+    EscrowExpired,
+    #[msg("The escrow has not expired")]
+    EscrowNotExpired,
+    #[msg("Escrow mints do not line up as opposite sides of the same pair")]
+    MintMismatch,
+    #[msg("Escrow does not belong to the given maker")]
+    MakerMismatch,
+    #[msg("The orders' prices do not cross for these amounts")]
+    OrdersDoNotCross,
+    #[msg("Price tiers must be at most 5, with strictly increasing boundaries")]
+    InvalidPriceTiers,
+    #[msg("All-or-none order must not hold more than the maximum fill")]
+    AllOrNoneExceedsMaxFill,
 } 
\ No newline at end of file
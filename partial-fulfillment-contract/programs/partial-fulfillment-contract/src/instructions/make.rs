@@ -5,7 +5,8 @@ use anchor_spl::{
     token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
-use crate::state::Escrow;
+use crate::state::{check_tiers, Escrow, OrderType, PriceTier, BPS_DENOMINATOR};
+use crate::instructions::EscrowError;
 
 #[derive(Accounts)]
 #[instruction(seed: u64)]
@@ -77,13 +78,48 @@ impl<'info> Make<'info> {
     }
 
     /// This function saves the escrow state after deposit
+    #[allow(clippy::too_many_arguments)]
     pub fn save_escrow(
         &mut self,
         seed: u64,
         deposit: u64,
-        receive_per_token: u64,
+        price_numerator: u64,
+        price_denominator: u64,
+        min_fill: Option<u64>,
+        max_fill: Option<u64>,
+        order_type: OrderType,
+        expires_at: Option<i64>,
+        tiers: Vec<PriceTier>,
         bumps: &MakeBumps,
     ) -> Result<()> {
+        require!(
+            price_numerator > 0 && price_denominator > 0,
+            EscrowError::InvalidPrice
+        );
+
+        check_tiers(&tiers)?;
+
+        let min_fill = min_fill.unwrap_or(0);
+        let max_fill = max_fill.unwrap_or(u64::MAX);
+        require!(
+            max_fill > 0 && min_fill <= max_fill,
+            EscrowError::InvalidFillLimits
+        );
+
+        if let Some(expires_at) = expires_at {
+            require!(
+                expires_at > Clock::get()?.unix_timestamp,
+                EscrowError::ExpiryInPast
+            );
+        }
+
+        if let OrderType::MinFillPercentage(bps) = order_type {
+            require!(
+                bps > 0 && bps as u64 <= BPS_DENOMINATOR,
+                EscrowError::InvalidOrderType
+            );
+        }
+
         self.escrow.set_inner(Escrow {
             seed,
             maker: self.maker.key(),
@@ -91,9 +127,18 @@ impl<'info> Make<'info> {
             mint_b: self.mint_b.key(),
             total_amount: deposit,
             remaining_amount: deposit,
-            receive_per_token,
+            price_numerator,
+            price_denominator,
+            price_version: 0,
+            min_fill,
+            max_fill,
+            order_type,
+            expires_at,
+            tiers,
+            created_slot: Clock::get()?.slot,
             bump: bumps.escrow,
         });
-        Ok(())
+
+        self.escrow.check_all_or_none_fits()
     }
 }
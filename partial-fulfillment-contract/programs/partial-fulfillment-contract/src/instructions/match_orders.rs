@@ -0,0 +1,306 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::state::{quote_match, Escrow, MatchQuote};
+use crate::instructions::EscrowError;
+
+#[derive(Accounts)]
+pub struct MatchOrders<'info> {
+    // Permissionless keeper crossing the two orders, keeps the spread
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    // Maker of escrow_x, which sells token A for token B
+    #[account(mut)]
+    pub maker_x: SystemAccount<'info>,
+
+    // Maker of escrow_y, which sells token B for token A
+    #[account(mut)]
+    pub maker_y: SystemAccount<'info>,
+
+    // Mints of token A and token B, from escrow_x's point of view
+    #[account(constraint = mint_a.key() != mint_b.key() @ EscrowError::MintMismatch)]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    // Escrow selling token A for token B
+    #[account(
+        mut,
+        constraint = escrow_x.maker == maker_x.key() @ EscrowError::MakerMismatch,
+        constraint = escrow_x.mint_a == mint_a.key() @ EscrowError::MintMismatch,
+        constraint = escrow_x.mint_b == mint_b.key() @ EscrowError::MintMismatch,
+        seeds = [b"escrow", maker_x.key().as_ref(), escrow_x.seed.to_le_bytes().as_ref()],
+        bump = escrow_x.bump
+    )]
+    pub escrow_x: Box<Account<'info, Escrow>>,
+
+    // Escrow selling token B for token A
+    #[account(
+        mut,
+        constraint = escrow_y.maker == maker_y.key() @ EscrowError::MakerMismatch,
+        constraint = escrow_y.mint_a == mint_b.key() @ EscrowError::MintMismatch,
+        constraint = escrow_y.mint_b == mint_a.key() @ EscrowError::MintMismatch,
+        seeds = [b"escrow", maker_y.key().as_ref(), escrow_y.seed.to_le_bytes().as_ref()],
+        bump = escrow_y.bump
+    )]
+    pub escrow_y: Box<Account<'info, Escrow>>,
+
+    // Vault of escrow_x holding token A
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow_x,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Vault of escrow_y holding token B
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = escrow_y,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Maker X's token B ATA (receives payment from vault_y)
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker_x,
+        associated_token::token_program = token_program,
+    )]
+    pub maker_x_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Maker Y's token A ATA (receives payment from vault_x)
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker_y,
+        associated_token::token_program = token_program,
+    )]
+    pub maker_y_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Keeper's token A ATA (receives the token A spread)
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        associated_token::mint = mint_a,
+        associated_token::authority = keeper,
+        associated_token::token_program = token_program,
+    )]
+    pub keeper_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Keeper's token B ATA (receives the token B spread)
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        associated_token::mint = mint_b,
+        associated_token::authority = keeper,
+        associated_token::token_program = token_program,
+    )]
+    pub keeper_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MatchOrders<'info> {
+    /// Settles amount_a of token A out of vault_x against amount_b of token B out of vault_y.
+    /// Each maker is paid at least their own price, so the trade only goes through when the prices cross;
+    /// whatever is left over on either side is the keeper's spread.
+    pub fn execute(&mut self, amount_a: u64, amount_b: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(!self.escrow_x.is_expired(now), EscrowError::EscrowExpired);
+        require!(!self.escrow_y.is_expired(now), EscrowError::EscrowExpired);
+
+        self.escrow_x.check_fill(amount_a)?;
+        self.escrow_y.check_fill(amount_b)?;
+
+        // What each maker is owed at their own price, and the keeper's spread on top
+        let MatchQuote {
+            owed_a_to_y,
+            owed_b_to_x,
+            spread_a,
+            spread_b,
+        } = quote_match(
+            &self.escrow_x,
+            &self.escrow_y,
+            amount_a,
+            amount_b,
+            self.mint_a.decimals,
+            self.mint_b.decimals,
+        )?;
+
+        // A side this match fills completely gets its vault closed, so its final fill also
+        // sweeps anything sent to the vault on top of the order to the keeper
+        let spread_a = if amount_a == self.escrow_x.remaining_amount {
+            self.vault_x.amount.checked_sub(owed_a_to_y).ok_or(EscrowError::Overflow)?
+        } else {
+            spread_a
+        };
+        let spread_b = if amount_b == self.escrow_y.remaining_amount {
+            self.vault_y.amount.checked_sub(owed_b_to_x).ok_or(EscrowError::Overflow)?
+        } else {
+            spread_b
+        };
+
+        // Build signer seeds for both escrow PDAs
+        let maker_x_key = self.maker_x.key();
+        let seed_x = self.escrow_x.seed.to_le_bytes();
+        let bump_x = [self.escrow_x.bump];
+        let signer_seeds_x: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            maker_x_key.as_ref(),
+            &seed_x[..],
+            &bump_x,
+        ]];
+
+        let maker_y_key = self.maker_y.key();
+        let seed_y = self.escrow_y.seed.to_le_bytes();
+        let bump_y = [self.escrow_y.bump];
+        let signer_seeds_y: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            maker_y_key.as_ref(),
+            &seed_y[..],
+            &bump_y,
+        ]];
+
+        // 1. Token A out of vault_x: maker Y's price, then the keeper's spread
+        self.transfer_from_vault(
+            self.vault_x.to_account_info(),
+            self.mint_a.to_account_info(),
+            self.maker_y_ata_a.to_account_info(),
+            self.escrow_x.to_account_info(),
+            owed_a_to_y,
+            self.mint_a.decimals,
+            &signer_seeds_x,
+        )?;
+        self.transfer_from_vault(
+            self.vault_x.to_account_info(),
+            self.mint_a.to_account_info(),
+            self.keeper_ata_a.to_account_info(),
+            self.escrow_x.to_account_info(),
+            spread_a,
+            self.mint_a.decimals,
+            &signer_seeds_x,
+        )?;
+
+        // 2. Token B out of vault_y: maker X's price, then the keeper's spread
+        self.transfer_from_vault(
+            self.vault_y.to_account_info(),
+            self.mint_b.to_account_info(),
+            self.maker_x_ata_b.to_account_info(),
+            self.escrow_y.to_account_info(),
+            owed_b_to_x,
+            self.mint_b.decimals,
+            &signer_seeds_y,
+        )?;
+        self.transfer_from_vault(
+            self.vault_y.to_account_info(),
+            self.mint_b.to_account_info(),
+            self.keeper_ata_b.to_account_info(),
+            self.escrow_y.to_account_info(),
+            spread_b,
+            self.mint_b.decimals,
+            &signer_seeds_y,
+        )?;
+
+        // 3. Update state
+        self.escrow_x.remaining_amount = self
+            .escrow_x
+            .remaining_amount
+            .checked_sub(amount_a)
+            .ok_or(EscrowError::Overflow)?;
+        self.escrow_y.remaining_amount = self
+            .escrow_y
+            .remaining_amount
+            .checked_sub(amount_b)
+            .ok_or(EscrowError::Overflow)?;
+
+        // 4. Close whichever side is fully filled, rent goes back to its maker
+        if self.escrow_x.remaining_amount == 0 {
+            self.close_vault(
+                self.vault_x.to_account_info(),
+                self.maker_x.to_account_info(),
+                self.escrow_x.to_account_info(),
+                &signer_seeds_x,
+            )?;
+            self.escrow_x.close(self.maker_x.to_account_info())?;
+        }
+        if self.escrow_y.remaining_amount == 0 {
+            self.close_vault(
+                self.vault_y.to_account_info(),
+                self.maker_y.to_account_info(),
+                self.escrow_y.to_account_info(),
+                &signer_seeds_y,
+            )?;
+            self.escrow_y.close(self.maker_y.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    /// Transfers out of a vault signed by its escrow, skipping zero amounts
+    #[allow(clippy::too_many_arguments)]
+    fn transfer_from_vault(
+        &self,
+        from: AccountInfo<'info>,
+        mint: AccountInfo<'info>,
+        to: AccountInfo<'info>,
+        authority: AccountInfo<'info>,
+        amount: u64,
+        decimals: u8,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let transfer = TransferChecked {
+            from,
+            mint,
+            to,
+            authority,
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            transfer,
+            signer_seeds,
+        );
+        transfer_checked(cpi_ctx, amount, decimals)
+    }
+
+    /// Closes an emptied vault signed by its escrow
+    fn close_vault(
+        &self,
+        account: AccountInfo<'info>,
+        destination: AccountInfo<'info>,
+        authority: AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let close = CloseAccount {
+            account,
+            destination,
+            authority,
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            close,
+            signer_seeds,
+        );
+        close_account(cpi_ctx)
+    }
+}
@@ -10,4 +10,16 @@ pub mod refund;
 pub use refund::*;
 
 pub mod take;
-pub use take::*;
\ No newline at end of file
+pub use take::*;
+
+pub mod update_price;
+pub use update_price::*;
+
+pub mod adjust;
+pub use adjust::*;
+
+pub mod refund_expired;
+pub use refund_expired::*;
+
+pub mod match_orders;
+pub use match_orders::*;
\ No newline at end of file
@@ -56,45 +56,56 @@ pub struct Refund<'info> {
 
 impl<'info> Refund<'info> {
     pub fn refund_and_close(&mut self) -> Result<()> {
-        // Build signer seeds for the escrow PDA
-        let maker_key = self.maker.key();
-        let signer_seeds: [&[&[u8]]; 1] = [&[
-            b"escrow",
-            maker_key.as_ref(),
-            &self.escrow.seed.to_le_bytes()[..],
-            &[self.escrow.bump],
-        ]];
-
-        // 1. Transfer remaining token A from vault to maker
-        let transfer = TransferChecked {
-            from: self.vault.to_account_info(),
-            mint: self.mint_a.to_account_info(),
-            to: self.maker_ata_a.to_account_info(),
-            authority: self.escrow.to_account_info(),
-        };
-
-        let ctx = CpiContext::new_with_signer(
-            self.token_program.to_account_info(),
-            transfer,
-            &signer_seeds,
-        );
-
-        // Transfer only the remaining amount
-        transfer_checked(ctx, self.escrow.remaining_amount, self.mint_a.decimals)?;
-
-        // 2. Close the vault account
-        let close = CloseAccount {
-            account: self.vault.to_account_info(),
-            destination: self.maker.to_account_info(),
-            authority: self.escrow.to_account_info(),
-        };
-
-        let ctx = CpiContext::new_with_signer(
-            self.token_program.to_account_info(),
-            close,
-            &signer_seeds,
-        );
-
-        close_account(ctx)
+        refund_and_close_vault(
+            &self.escrow,
+            &self.vault,
+            &self.mint_a,
+            &self.maker_ata_a,
+            self.maker.to_account_info(),
+            &self.token_program,
+        )
     }
 }
+
+// Sweeps everything left in the vault back to the maker and closes it, rent goes to the maker.
+// The whole vault balance is sent rather than escrow.remaining_amount, so tokens donated to the
+// vault can't leave a non-zero balance that blocks the close.
+pub fn refund_and_close_vault<'info>(
+    escrow: &Account<'info, Escrow>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    mint_a: &InterfaceAccount<'info, Mint>,
+    maker_ata_a: &InterfaceAccount<'info, TokenAccount>,
+    maker: AccountInfo<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<()> {
+    // Build signer seeds for the escrow PDA
+    let signer_seeds: [&[&[u8]]; 1] = [&[
+        b"escrow",
+        escrow.maker.as_ref(),
+        &escrow.seed.to_le_bytes()[..],
+        &[escrow.bump],
+    ]];
+
+    // 1. Transfer everything held by the vault to the maker
+    let transfer = TransferChecked {
+        from: vault.to_account_info(),
+        mint: mint_a.to_account_info(),
+        to: maker_ata_a.to_account_info(),
+        authority: escrow.to_account_info(),
+    };
+
+    let ctx = CpiContext::new_with_signer(token_program.to_account_info(), transfer, &signer_seeds);
+
+    transfer_checked(ctx, vault.amount, mint_a.decimals)?;
+
+    // 2. Close the vault account
+    let close = CloseAccount {
+        account: vault.to_account_info(),
+        destination: maker,
+        authority: escrow.to_account_info(),
+    };
+
+    let ctx = CpiContext::new_with_signer(token_program.to_account_info(), close, &signer_seeds);
+
+    close_account(ctx)
+}
@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::state::Escrow;
+use crate::instructions::{refund_and_close_vault, EscrowError};
+
+#[derive(Accounts)]
+pub struct RefundExpired<'info> {
+    // Anyone can trigger the refund once the escrow has expired
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    // Maker of the escrow (receives the tokens and the rent)
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    // Mint of token A (escrowed token)
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    // Maker's token A ATA (to receive refund), recreated at the caller's cost if the maker closed it
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+
+    // Escrow account
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        has_one = mint_a,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    // Vault account (holds token A)
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    // SPL program interfaces
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RefundExpired<'info> {
+    pub fn refund_and_close(&mut self) -> Result<()> {
+        require!(
+            self.escrow.is_expired(Clock::get()?.unix_timestamp),
+            EscrowError::EscrowNotExpired
+        );
+
+        refund_and_close_vault(
+            &self.escrow,
+            &self.vault,
+            &self.mint_a,
+            &self.maker_ata_a,
+            self.maker.to_account_info(),
+            &self.token_program,
+        )
+    }
+}
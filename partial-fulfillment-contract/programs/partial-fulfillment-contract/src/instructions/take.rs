@@ -8,7 +8,7 @@ use anchor_spl::{
     },
 };
 
-use crate::state::Escrow;
+use crate::state::{Escrow, FillReceipt};
 use crate::instructions::EscrowError;
 
 #[derive(Accounts)]
@@ -74,26 +74,96 @@ pub struct Take<'info> {
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
+    // Taker's fill history for this escrow, created on the first fill
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + FillReceipt::INIT_SPACE,
+        seeds = [
+            b"receipt",
+            escrow.key().as_ref(),
+            escrow.created_slot.to_le_bytes().as_ref(),
+            taker.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub fill_receipt: Box<Account<'info, FillReceipt>>,
+
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> Take<'info> {
-    pub fn execute(&mut self, amount_to_take: u64) -> Result<()> {
-        require!(amount_to_take > 0, EscrowError::InvalidAmount);
+    /// Taker names the token A amount, the token B cost is derived from the price.
+    /// Without fill_or_kill the amount is cut down to what the escrow can fill,
+    /// with it the take fails unless the whole amount is filled.
+    pub fn execute(
+        &mut self,
+        amount_to_take: u64,
+        price_version: u32,
+        fill_or_kill: bool,
+        bumps: &TakeBumps,
+    ) -> Result<()> {
+        self.check_takeable(price_version)?;
+
+        let amount_to_take = if fill_or_kill {
+            amount_to_take
+        } else {
+            self.escrow.clamp_fill(amount_to_take)
+        };
+        self.escrow.check_fill(amount_to_take)?;
+
+        // Calculate token B amount across the price tiers, each portion rounded up for the maker
+        let amount_b = self.escrow.quote_b(
+            amount_to_take,
+            self.mint_a.decimals,
+            self.mint_b.decimals,
+        )?;
+
+        self.settle(amount_to_take, amount_b, bumps)
+    }
+
+    /// Taker names the token B amount to spend, the token A received is derived from the price
+    pub fn execute_exact_b(
+        &mut self,
+        amount_b: u64,
+        min_amount_a: u64,
+        price_version: u32,
+        bumps: &TakeBumps,
+    ) -> Result<()> {
+        self.check_takeable(price_version)?;
+
+        // Calculate token A amount across the price tiers, rounded down for the maker
+        let amount_to_take = self.escrow.quote_a(
+            amount_b,
+            self.mint_a.decimals,
+            self.mint_b.decimals,
+        )?;
+
+        require!(amount_to_take >= min_amount_a, EscrowError::SlippageExceeded);
+        self.escrow.check_fill(amount_to_take)?;
+
+        self.settle(amount_to_take, amount_b, bumps)
+    }
+
+    /// Rejects the take if the escrow expired or the maker changed the price after the taker quoted it
+    fn check_takeable(&self, price_version: u32) -> Result<()> {
         require!(
-            amount_to_take <= self.escrow.remaining_amount,
-            EscrowError::ExceedsRemainingAmount
+            !self.escrow.is_expired(Clock::get()?.unix_timestamp),
+            EscrowError::EscrowExpired
         );
+        require!(
+            price_version == self.escrow.price_version,
+            EscrowError::PriceVersionMismatch
+        );
+        Ok(())
+    }
 
-        // Calculate token B amount: receive_per_token * amount_to_take
-        let amount_b = self
-            .escrow
-            .receive_per_token
-            .checked_mul(amount_to_take)
-            .ok_or(EscrowError::Overflow)?;
-
+    /// Moves amount_b of token B to the maker and amount_to_take of token A to the taker,
+    /// records the fill on the taker's receipt,
+    /// closing the vault and escrow once nothing remains
+    fn settle(&mut self, amount_to_take: u64, amount_b: u64, bumps: &TakeBumps) -> Result<()> {
         // 1. Transfer token B from taker to maker
         let transfer_b = TransferChecked {
             from: self.taker_ata_b.to_account_info(),
@@ -135,7 +205,10 @@ impl<'info> Take<'info> {
             .checked_sub(amount_to_take)
             .ok_or(EscrowError::Overflow)?;
 
-        // 4. If everything is taken, close vault and escrow
+        // 4. Record the fill on the taker's receipt
+        self.record_fill(amount_to_take, amount_b, bumps)?;
+
+        // 5. If everything is taken, close vault and escrow
         if self.escrow.remaining_amount == 0 {
             // Close vault
             let close = CloseAccount {
@@ -157,4 +230,19 @@ impl<'info> Take<'info> {
 
         Ok(())
     }
+
+    /// Adds this fill to the taker's running totals, filling in the identity fields on first use
+    fn record_fill(&mut self, amount_a: u64, amount_b: u64, bumps: &TakeBumps) -> Result<()> {
+        let receipt = &mut self.fill_receipt;
+
+        receipt.escrow = self.escrow.key();
+        receipt.escrow_created_slot = self.escrow.created_slot;
+        receipt.taker = self.taker.key();
+        receipt.maker = self.maker.key();
+        receipt.mint_a = self.mint_a.key();
+        receipt.mint_b = self.mint_b.key();
+        receipt.bump = bumps.fill_receipt;
+
+        receipt.record(amount_a, amount_b, Clock::get()?.unix_timestamp)
+    }
 }
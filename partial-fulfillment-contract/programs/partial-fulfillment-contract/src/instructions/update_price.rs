@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{check_tiers, Escrow, PriceTier};
+use crate::instructions::EscrowError;
+
+#[derive(Accounts)]
+pub struct UpdatePrice<'info> {
+    // Maker of the escrow
+    pub maker: Signer<'info>,
+
+    // Escrow account
+    #[account(
+        mut,
+        has_one = maker,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+}
+
+impl<'info> UpdatePrice<'info> {
+    /// Sets a new base price and price tiers for the remaining amount.
+    /// The version bump makes takes quoted against the old price fail.
+    pub fn update_price(
+        &mut self,
+        price_numerator: u64,
+        price_denominator: u64,
+        tiers: Vec<PriceTier>,
+    ) -> Result<()> {
+        require!(
+            price_numerator > 0 && price_denominator > 0,
+            EscrowError::InvalidPrice
+        );
+        check_tiers(&tiers)?;
+
+        self.escrow.price_numerator = price_numerator;
+        self.escrow.price_denominator = price_denominator;
+        self.escrow.tiers = tiers;
+        self.escrow.price_version = self
+            .escrow
+            .price_version
+            .checked_add(1)
+            .ok_or(EscrowError::Overflow)?;
+
+        Ok(())
+    }
+}
@@ -4,6 +4,7 @@ pub mod state;
 pub mod instructions;
 
 use instructions::*;
+use state::{OrderType, PriceTier};
 
 declare_id!("7PjmkG4xTrQfM3rtGu6cA7B27eLBy56qJZ5gAq7NpCYH");
 
@@ -11,19 +12,95 @@ declare_id!("7PjmkG4xTrQfM3rtGu6cA7B27eLBy56qJZ5gAq7NpCYH");
 pub mod partial_fulfillment_escrow {
     use super::*;
 
-    /// Maker initializes escrow and deposits token A
-    pub fn make(ctx: Context<Make>, seed: u64, deposit: u64, receive: u64) -> Result<()> {
+    /// Maker initializes escrow and deposits token A,
+    /// asking price_numerator / price_denominator whole token B per whole token A,
+    /// optionally bounding the token A size of each fill, choosing the order type and an expiry,
+    /// tiers price the first fills by volume before the base price applies
+    #[allow(clippy::too_many_arguments)]
+    pub fn make(
+        ctx: Context<Make>,
+        seed: u64,
+        deposit: u64,
+        price_numerator: u64,
+        price_denominator: u64,
+        min_fill: Option<u64>,
+        max_fill: Option<u64>,
+        order_type: OrderType,
+        expires_at: Option<i64>,
+        tiers: Vec<PriceTier>,
+    ) -> Result<()> {
         ctx.accounts.deposit(deposit)?;
-        ctx.accounts.save_escrow(seed, deposit, receive, &ctx.bumps)
+        ctx.accounts.save_escrow(
+            seed,
+            deposit,
+            price_numerator,
+            price_denominator,
+            min_fill,
+            max_fill,
+            order_type,
+            expires_at,
+            tiers,
+            &ctx.bumps,
+        )
     }
 
-    /// Taker partially fulfills the order with token B and receives proportional token A
-    pub fn take(ctx: Context<Take>, amount_b: u64) -> Result<()> {
-        ctx.accounts.execute(amount_b)
+    /// Taker takes amount_a of token A and pays the maker's price in token B,
+    /// price_version must match the escrow's current price version,
+    /// fill_or_kill rejects the take instead of filling less than amount_a
+    pub fn take(
+        ctx: Context<Take>,
+        amount_a: u64,
+        price_version: u32,
+        fill_or_kill: bool,
+    ) -> Result<()> {
+        ctx.accounts
+            .execute(amount_a, price_version, fill_or_kill, &ctx.bumps)
+    }
+
+    /// Taker spends exactly amount_b of token B and receives at least min_amount_a of token A
+    pub fn take_exact_b(
+        ctx: Context<Take>,
+        amount_b: u64,
+        min_amount_a: u64,
+        price_version: u32,
+    ) -> Result<()> {
+        ctx.accounts
+            .execute_exact_b(amount_b, min_amount_a, price_version, &ctx.bumps)
+    }
+
+    /// Maker requotes the remaining amount, base price and tiers, and bumps the price version
+    pub fn update_price(
+        ctx: Context<UpdatePrice>,
+        price_numerator: u64,
+        price_denominator: u64,
+        tiers: Vec<PriceTier>,
+    ) -> Result<()> {
+        ctx.accounts
+            .update_price(price_numerator, price_denominator, tiers)
+    }
+
+    /// Maker pulls part of the remaining token A back without closing the escrow
+    pub fn withdraw(ctx: Context<Adjust>, amount: u64) -> Result<()> {
+        ctx.accounts.withdraw(amount)
+    }
+
+    /// Maker deposits more token A into an open escrow
+    pub fn top_up(ctx: Context<Adjust>, amount: u64) -> Result<()> {
+        ctx.accounts.top_up(amount)
+    }
+
+    /// Keeper crosses an A->B escrow with a B->A escrow vault to vault and keeps the spread
+    pub fn match_orders(ctx: Context<MatchOrders>, amount_a: u64, amount_b: u64) -> Result<()> {
+        ctx.accounts.execute(amount_a, amount_b)
     }
 
     /// Maker claims refund of remaining token A and closes the vault + escrow
     pub fn refund(ctx: Context<Refund>) -> Result<()> {
         ctx.accounts.refund_and_close()
     }
+
+    /// Anyone returns the unfilled remainder of an expired escrow to the maker and closes it
+    pub fn refund_expired(ctx: Context<RefundExpired>) -> Result<()> {
+        ctx.accounts.refund_and_close()
+    }
 }
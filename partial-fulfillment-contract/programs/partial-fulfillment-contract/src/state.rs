@@ -2,10 +2,12 @@
 
 use anchor_lang::prelude::*;
 
+use crate::instructions::EscrowError;
+
 /// This is the state of the escrow account.
 /// It stores how much the maker deposited,
 /// how much has been taken,
-/// and how much token B is expected in return.
+/// and the price in token B asked for token A.
 
 #[account]
 #[derive(InitSpace)]
@@ -16,6 +18,664 @@ pub struct Escrow {
     pub mint_b: Pubkey,           // token expected from takers
     pub total_amount: u64,        // total amount deposited (token A)
     pub remaining_amount: u64,    // remaining token A not yet taken
-    pub receive_per_token: u64,   // how much token B the maker wants for 1 token A
+    pub price_numerator: u64,     // price of 1 whole token A in whole token B is
+    pub price_denominator: u64,   // price_numerator / price_denominator, independent of decimals
+    pub price_version: u32,       // bumped on every price update, takers must quote the version they priced against
+    pub min_fill: u64,            // smallest token A amount a single take can fill (0 = no minimum)
+    pub max_fill: u64,            // largest token A amount a single take can fill (u64::MAX = no maximum)
+    pub order_type: OrderType,    // how much of the order a single take must fill
+    pub expires_at: Option<i64>,  // unix timestamp after which takes are rejected and anyone can refund (None = never)
+    #[max_len(5)]
+    pub tiers: Vec<PriceTier>,    // volume tiers priced before the base price, at most MAX_PRICE_TIERS
+    pub created_slot: u64,        // slot the escrow was made in, tells apart escrows re-made with the same seed
+    pub bump: u8,                 // PDA bump
+}
+
+/// Most price tiers an escrow can carry, matches the max_len on Escrow::tiers
+pub const MAX_PRICE_TIERS: usize = 5;
+
+/// One volume tier: its price applies while the escrow's filled amount is below up_to.
+/// Tiers are ordered by up_to, so the first tier covers the first units filled.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]
+pub struct PriceTier {
+    pub up_to: u64,               // cumulative token A filled at which this tier ends
+    pub price_numerator: u64,     // price of 1 whole token A in whole token B for this tier
+    pub price_denominator: u64,
+}
+
+/// Checks that tiers fit in the account, are strictly increasing and carry a valid price
+pub fn check_tiers(tiers: &[PriceTier]) -> Result<()> {
+    require!(tiers.len() <= MAX_PRICE_TIERS, EscrowError::InvalidPriceTiers);
+
+    let mut previous = 0;
+    for tier in tiers {
+        require!(tier.up_to > previous, EscrowError::InvalidPriceTiers);
+        require!(
+            tier.price_numerator > 0 && tier.price_denominator > 0,
+            EscrowError::InvalidPrice
+        );
+        previous = tier.up_to;
+    }
+
+    Ok(())
+}
+
+/// Basis points in 100%, used by OrderType::MinFillPercentage
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// How the maker allows the order to be filled
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]
+pub enum OrderType {
+    Partial,                      // any fill within min_fill / max_fill
+    AllOrNone,                    // a single take must fill everything that remains
+    MinFillPercentage(u16),       // each fill must be at least this many basis points of total_amount
+}
+
+impl Escrow {
+    /// Token B base units owed for `amount_a` base units of token A.
+    /// The fill starts at the amount already filled and walks the price tiers,
+    /// each portion is priced at its own tier and the base price applies past the last tier.
+    pub fn quote_b(&self, amount_a: u64, decimals_a: u8, decimals_b: u8) -> Result<u64> {
+        let mut filled = self.filled_amount()?;
+        let mut left = amount_a;
+        let mut amount_b: u64 = 0;
+
+        for (up_to, numerator, denominator) in self.price_segments() {
+            if left == 0 {
+                break;
+            }
+            if filled >= up_to {
+                continue;
+            }
+
+            let portion = left.min(up_to - filled);
+            let cost = quote_b_at(portion, numerator, denominator, decimals_a, decimals_b)?;
+            amount_b = amount_b.checked_add(cost).ok_or(EscrowError::Overflow)?;
+            filled += portion;
+            left -= portion;
+        }
+
+        Ok(amount_b)
+    }
+
+    /// Token A base units bought by spending `amount_b` base units of token B,
+    /// the inverse of quote_b: whole tiers are bought while the budget covers them,
+    /// the rest of the budget buys at the tier it runs out in.
+    pub fn quote_a(&self, amount_b: u64, decimals_a: u8, decimals_b: u8) -> Result<u64> {
+        let mut filled = self.filled_amount()?;
+        let mut budget = amount_b;
+        let mut amount_a: u64 = 0;
+
+        for (up_to, numerator, denominator) in self.price_segments() {
+            if filled >= up_to {
+                continue;
+            }
+
+            // The base price has no upper bound, the rest of the budget is spent there
+            if up_to < u64::MAX {
+                let capacity = up_to - filled;
+                let cost = quote_b_at(capacity, numerator, denominator, decimals_a, decimals_b)?;
+                if cost <= budget {
+                    amount_a = amount_a.checked_add(capacity).ok_or(EscrowError::Overflow)?;
+                    budget -= cost;
+                    filled = up_to;
+                    continue;
+                }
+            }
+
+            let bought = quote_a_at(budget, numerator, denominator, decimals_a, decimals_b)?;
+            amount_a = amount_a.checked_add(bought).ok_or(EscrowError::Overflow)?;
+            break;
+        }
+
+        Ok(amount_a)
+    }
+
+    /// Token A already filled out of the escrow, the position the price tiers are measured from.
+    /// Withdraw and top-up move total and remaining together, so they do not shift it.
+    pub fn filled_amount(&self) -> Result<u64> {
+        self.total_amount
+            .checked_sub(self.remaining_amount)
+            .ok_or(EscrowError::Overflow.into())
+    }
+
+    /// The tiers followed by the base price, as (filled amount the price applies below, numerator, denominator)
+    fn price_segments(&self) -> impl Iterator<Item = (u64, u64, u64)> + '_ {
+        self.tiers
+            .iter()
+            .map(|tier| (tier.up_to, tier.price_numerator, tier.price_denominator))
+            .chain(std::iter::once((
+                u64::MAX,
+                self.price_numerator,
+                self.price_denominator,
+            )))
+    }
+
+    /// Whether the escrow has passed its expiry, escrows without one never expire
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
+    /// Checks a fill of `amount_a` against the remaining amount and the fill size limits.
+    /// Taking the whole remainder is always allowed when it is below min_fill,
+    /// so the last dust in the vault can still be cleared.
+    pub fn check_fill(&self, amount_a: u64) -> Result<()> {
+        require!(amount_a > 0, EscrowError::InvalidAmount);
+        require!(
+            amount_a <= self.remaining_amount,
+            EscrowError::ExceedsRemainingAmount
+        );
+
+        let is_final_dust = amount_a == self.remaining_amount && amount_a < self.min_fill;
+        require!(
+            amount_a >= self.min_fill || is_final_dust,
+            EscrowError::FillBelowMinimum
+        );
+        require!(amount_a <= self.max_fill, EscrowError::FillAboveMaximum);
+
+        match self.order_type {
+            OrderType::Partial => {}
+            OrderType::AllOrNone => require!(
+                amount_a == self.remaining_amount,
+                EscrowError::AllOrNoneRequiresFullFill
+            ),
+            OrderType::MinFillPercentage(bps) => {
+                let min_amount = (self.total_amount as u128 * bps as u128)
+                    .div_ceil(BPS_DENOMINATOR as u128);
+                require!(
+                    amount_a as u128 >= min_amount || amount_a == self.remaining_amount,
+                    EscrowError::FillBelowMinimumPercentage
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// An all-or-none order is only ever taken whole, so its remainder must fit in a single max_fill
+    pub fn check_all_or_none_fits(&self) -> Result<()> {
+        if self.order_type == OrderType::AllOrNone {
+            require!(
+                self.remaining_amount <= self.max_fill,
+                EscrowError::AllOrNoneExceedsMaxFill
+            );
+        }
+        Ok(())
+    }
+
+    /// The largest fill a take asking for `amount_a` can get: capped by the remaining amount and max_fill.
+    /// Used when the taker accepts a partial outcome instead of fill-or-kill.
+    pub fn clamp_fill(&self, amount_a: u64) -> u64 {
+        amount_a.min(self.remaining_amount).min(self.max_fill)
+    }
+}
+
+/// amount_b = amount_a * numerator * 10^decimals_b / (denominator * 10^decimals_a),
+/// the price is in whole tokens so both mints' decimals are applied,
+/// rounded up so the maker never receives less than the price.
+fn quote_b_at(
+    amount_a: u64,
+    price_numerator: u64,
+    price_denominator: u64,
+    decimals_a: u8,
+    decimals_b: u8,
+) -> Result<u64> {
+    let numerator = (amount_a as u128)
+        .checked_mul(price_numerator as u128)
+        .and_then(|n| n.checked_mul(10u128.checked_pow(decimals_b as u32)?))
+        .ok_or(EscrowError::Overflow)?;
+    let denominator = (price_denominator as u128)
+        .checked_mul(10u128.checked_pow(decimals_a as u32).ok_or(EscrowError::Overflow)?)
+        .ok_or(EscrowError::Overflow)?;
+
+    let amount_b = numerator.div_ceil(denominator);
+    u64::try_from(amount_b).map_err(|_| EscrowError::Overflow.into())
+}
+
+/// The inverse of quote_b_at at a single price, rounded down so the maker never receives less than the price
+fn quote_a_at(
+    amount_b: u64,
+    price_numerator: u64,
+    price_denominator: u64,
+    decimals_a: u8,
+    decimals_b: u8,
+) -> Result<u64> {
+    let numerator = (amount_b as u128)
+        .checked_mul(price_denominator as u128)
+        .and_then(|n| n.checked_mul(10u128.checked_pow(decimals_a as u32)?))
+        .ok_or(EscrowError::Overflow)?;
+    let denominator = (price_numerator as u128)
+        .checked_mul(10u128.checked_pow(decimals_b as u32).ok_or(EscrowError::Overflow)?)
+        .ok_or(EscrowError::Overflow)?;
+
+    let amount_a = numerator / denominator;
+    u64::try_from(amount_a).map_err(|_| EscrowError::Overflow.into())
+}
+
+/// What a match of two crossing orders pays out: each maker their own price, the keeper the rest
+#[derive(Debug, PartialEq, Eq)]
+pub struct MatchQuote {
+    pub owed_a_to_y: u64,         // token A owed to escrow_y's maker at escrow_y's price
+    pub owed_b_to_x: u64,         // token B owed to escrow_x's maker at escrow_x's price
+    pub spread_a: u64,            // token A left over for the keeper
+    pub spread_b: u64,            // token B left over for the keeper
+}
+
+/// Prices a match of amount_a of escrow_x's token A against amount_b of escrow_y's token B.
+/// Each side is quoted at its own maker's price, rounded up in that maker's favor,
+/// and the match only goes through when both makers are paid in full.
+pub fn quote_match(
+    escrow_x: &Escrow,
+    escrow_y: &Escrow,
+    amount_a: u64,
+    amount_b: u64,
+    decimals_a: u8,
+    decimals_b: u8,
+) -> Result<MatchQuote> {
+    let owed_b_to_x = escrow_x.quote_b(amount_a, decimals_a, decimals_b)?;
+    let owed_a_to_y = escrow_y.quote_b(amount_b, decimals_b, decimals_a)?;
+
+    require!(amount_b >= owed_b_to_x, EscrowError::OrdersDoNotCross);
+    require!(amount_a >= owed_a_to_y, EscrowError::OrdersDoNotCross);
+
+    Ok(MatchQuote {
+        owed_a_to_y,
+        owed_b_to_x,
+        spread_a: amount_a - owed_a_to_y,
+        spread_b: amount_b - owed_b_to_x,
+    })
+}
+
+/// Running totals of one taker's fills against one escrow.
+/// It is a separate PDA so the history survives after the escrow is closed.
+#[account]
+#[derive(InitSpace)]
+pub struct FillReceipt {
+    pub escrow: Pubkey,           // escrow that was filled
+    pub escrow_created_slot: u64, // created_slot of that escrow, so a re-made escrow gets a fresh receipt
+    pub taker: Pubkey,            // taker the receipt belongs to
+    pub maker: Pubkey,            // maker of the escrow
+    pub mint_a: Pubkey,           // token received by the taker
+    pub mint_b: Pubkey,           // token paid by the taker
+    pub amount_a_received: u64,   // cumulative token A received by the taker
+    pub amount_b_paid: u64,       // cumulative token B paid to the maker
+    pub fill_count: u64,          // number of fills
+    pub last_fill_at: i64,        // unix timestamp of the latest fill
     pub bump: u8,                 // PDA bump
 }
+
+impl FillReceipt {
+    /// Adds one fill to the running totals
+    pub fn record(&mut self, amount_a: u64, amount_b: u64, now: i64) -> Result<()> {
+        self.amount_a_received = self
+            .amount_a_received
+            .checked_add(amount_a)
+            .ok_or(EscrowError::Overflow)?;
+        self.amount_b_paid = self
+            .amount_b_paid
+            .checked_add(amount_b)
+            .ok_or(EscrowError::Overflow)?;
+        self.fill_count = self.fill_count.checked_add(1).ok_or(EscrowError::Overflow)?;
+        self.last_fill_at = now;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn escrow(
+        total_amount: u64,
+        remaining_amount: u64,
+        price: (u64, u64),
+        tiers: &[PriceTier],
+    ) -> Escrow {
+        Escrow {
+            seed: 0,
+            maker: Pubkey::default(),
+            mint_a: Pubkey::default(),
+            mint_b: Pubkey::default(),
+            total_amount,
+            remaining_amount,
+            price_numerator: price.0,
+            price_denominator: price.1,
+            price_version: 0,
+            min_fill: 0,
+            max_fill: u64::MAX,
+            order_type: OrderType::Partial,
+            expires_at: None,
+            tiers: tiers.to_vec(),
+            created_slot: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn quote_b_at_applies_both_mints_decimals() {
+        // 1.5 B per A, with 9 decimal A and 6 decimal B
+        assert_eq!(quote_b_at(1_000_000_000, 3, 2, 9, 6).unwrap(), 1_500_000);
+        assert_eq!(quote_b_at(2_000_000_000, 3, 2, 9, 6).unwrap(), 3_000_000);
+
+        // and the other way round, 6 decimal A and 9 decimal B
+        assert_eq!(quote_b_at(1_000_000, 3, 2, 6, 9).unwrap(), 1_500_000_000);
+
+        // sub-unit prices: 0.001 B per A
+        assert_eq!(quote_b_at(5_000, 1, 1_000, 0, 0).unwrap(), 5);
+    }
+
+    #[test]
+    fn quote_b_at_rounds_up_for_the_maker() {
+        // 1 base unit of A is worth 0.0015 base units of B, the taker still pays 1
+        assert_eq!(quote_b_at(1, 3, 2, 9, 6).unwrap(), 1);
+        assert_eq!(quote_b_at(1_000_000_001, 3, 2, 9, 6).unwrap(), 1_500_001);
+
+        // exact results are not bumped
+        assert_eq!(quote_b_at(2, 3, 2, 0, 0).unwrap(), 3);
+        assert_eq!(quote_b_at(3, 3, 2, 0, 0).unwrap(), 5);
+        assert_eq!(quote_b_at(0, 3, 2, 9, 6).unwrap(), 0);
+    }
+
+    #[test]
+    fn quote_b_at_reports_overflow() {
+        // the result does not fit in a u64
+        assert_eq!(
+            quote_b_at(u64::MAX, 2, 1, 0, 0).unwrap_err(),
+            EscrowError::Overflow.into()
+        );
+        // u128 intermediates keep a large but representable result exact
+        assert_eq!(
+            quote_b_at(u64::MAX, 1_000_000_000, 1_000_000_000, 9, 9).unwrap(),
+            u64::MAX
+        );
+        // 10^decimals itself overflows
+        assert_eq!(
+            quote_b_at(1, 1, 1, 0, 40).unwrap_err(),
+            EscrowError::Overflow.into()
+        );
+    }
+
+    #[test]
+    fn quote_b_uses_the_base_price_without_tiers() {
+        let escrow = escrow(10_000_000_000, 10_000_000_000, (3, 2), &[]);
+        assert_eq!(escrow.quote_b(1_000_000_000, 9, 6).unwrap(), 1_500_000);
+        assert_eq!(escrow.quote_b(1, 9, 6).unwrap(), 1);
+    }
+
+    fn limited(remaining_amount: u64, min_fill: u64, max_fill: u64) -> Escrow {
+        Escrow {
+            min_fill,
+            max_fill,
+            ..escrow(1_000, remaining_amount, (1, 1), &[])
+        }
+    }
+
+    #[test]
+    fn check_fill_enforces_the_fill_size_limits() {
+        let escrow = limited(1_000, 100, 500);
+
+        assert!(escrow.check_fill(100).is_ok());
+        assert!(escrow.check_fill(500).is_ok());
+        assert_eq!(escrow.check_fill(99).unwrap_err(), EscrowError::FillBelowMinimum.into());
+        assert_eq!(escrow.check_fill(501).unwrap_err(), EscrowError::FillAboveMaximum.into());
+        assert_eq!(escrow.check_fill(0).unwrap_err(), EscrowError::InvalidAmount.into());
+    }
+
+    #[test]
+    fn check_fill_rejects_more_than_remains() {
+        let escrow = limited(300, 0, u64::MAX);
+
+        assert!(escrow.check_fill(300).is_ok());
+        assert_eq!(
+            escrow.check_fill(301).unwrap_err(),
+            EscrowError::ExceedsRemainingAmount.into()
+        );
+    }
+
+    #[test]
+    fn check_fill_lets_the_final_dust_below_min_fill_through() {
+        let escrow = limited(40, 100, 500);
+
+        // only the whole remainder clears the dust, part of it is still below the minimum
+        assert!(escrow.check_fill(40).is_ok());
+        assert_eq!(escrow.check_fill(39).unwrap_err(), EscrowError::FillBelowMinimum.into());
+    }
+
+    #[test]
+    fn quote_a_at_rounds_down_for_the_maker() {
+        // 1.5 B per A, with 9 decimal A and 6 decimal B
+        assert_eq!(quote_a_at(1_500_000, 3, 2, 9, 6).unwrap(), 1_000_000_000);
+
+        // 1 base unit of B buys 666.66.. base units of A, the fraction stays with the maker
+        assert_eq!(quote_a_at(1, 3, 2, 9, 6).unwrap(), 666);
+        assert_eq!(quote_a_at(1, 3, 2, 0, 0).unwrap(), 0);
+        assert_eq!(quote_a_at(5, 3, 2, 0, 0).unwrap(), 3);
+
+        assert_eq!(
+            quote_a_at(u64::MAX, 1, 2, 0, 0).unwrap_err(),
+            EscrowError::Overflow.into()
+        );
+    }
+
+    #[test]
+    fn quote_a_never_costs_more_than_the_budget() {
+        let escrow = escrow(u64::MAX, u64::MAX, (7, 3), &[]);
+
+        for amount_b in [1, 2, 3, 7, 10, 999, 1_000_001] {
+            let amount_a = escrow.quote_a(amount_b, 6, 9).unwrap();
+            // what the taker gets is worth at most what they pay, and one more unit would cost more
+            assert!(escrow.quote_b(amount_a, 6, 9).unwrap() <= amount_b);
+            assert!(escrow.quote_b(amount_a + 1, 6, 9).unwrap() > amount_b);
+        }
+    }
+
+    fn tier(up_to: u64, price_numerator: u64, price_denominator: u64) -> PriceTier {
+        PriceTier { up_to, price_numerator, price_denominator }
+    }
+
+    // 2 B per A for the first 100 A, 1.5 B per A up to 300 A, then 1 B per A
+    fn tiered(total_amount: u64, remaining_amount: u64) -> Escrow {
+        escrow(total_amount, remaining_amount, (1, 1), &[tier(100, 2, 1), tier(300, 3, 2)])
+    }
+
+    #[test]
+    fn quote_b_walks_the_tiers() {
+        let escrow = tiered(1_000, 1_000);
+
+        assert_eq!(escrow.quote_b(50, 0, 0).unwrap(), 100);
+        assert_eq!(escrow.quote_b(100, 0, 0).unwrap(), 200);
+        assert_eq!(escrow.quote_b(101, 0, 0).unwrap(), 202);
+        assert_eq!(escrow.quote_b(150, 0, 0).unwrap(), 200 + 75);
+        assert_eq!(escrow.quote_b(300, 0, 0).unwrap(), 200 + 300);
+        assert_eq!(escrow.quote_b(400, 0, 0).unwrap(), 200 + 300 + 100);
+    }
+
+    #[test]
+    fn quote_b_starts_from_the_amount_already_filled() {
+        // 50 A filled, the next fill finishes the first tier and continues into the second
+        assert_eq!(tiered(1_000, 950).quote_b(100, 0, 0).unwrap(), 100 + 75);
+        // filled up to the boundary, the second tier applies from the first unit
+        assert_eq!(tiered(1_000, 900).quote_b(2, 0, 0).unwrap(), 3);
+        // past every tier only the base price is left
+        assert_eq!(tiered(1_000, 600).quote_b(100, 0, 0).unwrap(), 100);
+    }
+
+    #[test]
+    fn quote_b_rounds_up_every_tier_portion() {
+        // a third of a B per A, both in the tier and at the base price
+        let escrow = escrow(10, 10, (1, 3), &[tier(1, 1, 3)]);
+
+        assert_eq!(escrow.quote_b(1, 0, 0).unwrap(), 1);
+        assert_eq!(escrow.quote_b(2, 0, 0).unwrap(), 2);
+        assert_eq!(escrow.quote_b(4, 0, 0).unwrap(), 2);
+    }
+
+    #[test]
+    fn quote_b_reports_overflow_across_tiers() {
+        // each portion fits in a u64 but their sum does not
+        let escrow = escrow(10, 10, (u64::MAX, 1), &[tier(1, u64::MAX, 1)]);
+
+        assert_eq!(escrow.quote_b(1, 0, 0).unwrap(), u64::MAX);
+        assert_eq!(escrow.quote_b(2, 0, 0).unwrap_err(), EscrowError::Overflow.into());
+    }
+
+    #[test]
+    fn quote_a_buys_whole_tiers_then_spends_the_rest() {
+        let escrow = tiered(1_000, 1_000);
+
+        assert_eq!(escrow.quote_a(200, 0, 0).unwrap(), 100);
+        assert_eq!(escrow.quote_a(275, 0, 0).unwrap(), 150);
+        // 74 B left in the second tier buys 49.33.. A, rounded down
+        assert_eq!(escrow.quote_a(274, 0, 0).unwrap(), 149);
+        assert_eq!(escrow.quote_a(600, 0, 0).unwrap(), 400);
+        assert_eq!(escrow.quote_a(0, 0, 0).unwrap(), 0);
+
+        // starting halfway through the first tier
+        assert_eq!(tiered(1_000, 950).quote_a(175, 0, 0).unwrap(), 100);
+    }
+
+    #[test]
+    fn quote_a_inverts_quote_b_across_tiers() {
+        let escrow = tiered(1_000, 1_000);
+
+        for amount_a in [1, 99, 100, 101, 299, 300, 301, 700] {
+            let amount_b = escrow.quote_b(amount_a, 0, 0).unwrap();
+            assert_eq!(escrow.quote_a(amount_b, 0, 0).unwrap(), amount_a);
+        }
+    }
+
+    #[test]
+    fn check_tiers_rejects_invalid_tiers() {
+        assert!(check_tiers(&[]).is_ok());
+        assert!(check_tiers(&[tier(100, 2, 1), tier(300, 3, 2)]).is_ok());
+
+        let too_many: Vec<PriceTier> =
+            (1..=MAX_PRICE_TIERS as u64 + 1).map(|up_to| tier(up_to, 1, 1)).collect();
+        assert_eq!(check_tiers(&too_many).unwrap_err(), EscrowError::InvalidPriceTiers.into());
+
+        assert_eq!(
+            check_tiers(&[tier(300, 2, 1), tier(100, 3, 2)]).unwrap_err(),
+            EscrowError::InvalidPriceTiers.into()
+        );
+        assert_eq!(
+            check_tiers(&[tier(100, 2, 1), tier(100, 3, 2)]).unwrap_err(),
+            EscrowError::InvalidPriceTiers.into()
+        );
+        assert_eq!(check_tiers(&[tier(0, 2, 1)]).unwrap_err(), EscrowError::InvalidPriceTiers.into());
+        assert_eq!(check_tiers(&[tier(100, 0, 1)]).unwrap_err(), EscrowError::InvalidPrice.into());
+        assert_eq!(check_tiers(&[tier(100, 1, 0)]).unwrap_err(), EscrowError::InvalidPrice.into());
+    }
+
+    #[test]
+    fn quote_match_rejects_orders_that_do_not_cross() {
+        // X sells A at 2 B, Y sells B at 1 A each, i.e. buys A at 1 B: X asks more than Y bids
+        let x = escrow(1_000, 1_000, (2, 1), &[]);
+        let y = escrow(1_000, 1_000, (1, 1), &[]);
+
+        // X is paid 200 B for 100 A only if 200 B comes out of Y, which wants 200 A for it
+        assert_eq!(
+            quote_match(&x, &y, 100, 150, 0, 0).unwrap_err(),
+            EscrowError::OrdersDoNotCross.into()
+        );
+        assert_eq!(
+            quote_match(&x, &y, 100, 200, 0, 0).unwrap_err(),
+            EscrowError::OrdersDoNotCross.into()
+        );
+    }
+
+    #[test]
+    fn quote_match_at_an_exact_cross_leaves_no_spread() {
+        // X sells A at 2 B, Y sells B at 0.5 A
+        let x = escrow(1_000, 1_000, (2, 1), &[]);
+        let y = escrow(1_000, 1_000, (1, 2), &[]);
+
+        assert_eq!(
+            quote_match(&x, &y, 100, 200, 0, 0).unwrap(),
+            MatchQuote { owed_a_to_y: 100, owed_b_to_x: 200, spread_a: 0, spread_b: 0 }
+        );
+    }
+
+    #[test]
+    fn quote_match_gives_the_keeper_the_spread() {
+        // X sells A at 1 B, Y sells B at 0.5 A: 100 A against 150 B leaves 25 A and 50 B over
+        let x = escrow(1_000, 1_000, (1, 1), &[]);
+        let y = escrow(1_000, 1_000, (1, 2), &[]);
+
+        assert_eq!(
+            quote_match(&x, &y, 100, 150, 0, 0).unwrap(),
+            MatchQuote { owed_a_to_y: 75, owed_b_to_x: 100, spread_a: 25, spread_b: 50 }
+        );
+    }
+
+    #[test]
+    fn quote_match_rounds_each_maker_up() {
+        // X sells A at 1/3 B, Y sells B at 3 A: exactly crossing, but only in whole tokens
+        let x = escrow(1_000, 1_000, (1, 3), &[]);
+        let y = escrow(1_000, 1_000, (3, 1), &[]);
+
+        // 10 A is worth 3.33.. B, X is owed 4 and 4 B costs Y 12 A, more than the 10 on offer
+        assert_eq!(
+            quote_match(&x, &y, 10, 4, 0, 0).unwrap_err(),
+            EscrowError::OrdersDoNotCross.into()
+        );
+        // 12 A against 4 B crosses exactly
+        assert_eq!(
+            quote_match(&x, &y, 12, 4, 0, 0).unwrap(),
+            MatchQuote { owed_a_to_y: 12, owed_b_to_x: 4, spread_a: 0, spread_b: 0 }
+        );
+        // 13 A is owed 4.33.. B, rounded up to 5, which Y only sells for 15 A
+        assert_eq!(
+            quote_match(&x, &y, 13, 4, 0, 0).unwrap_err(),
+            EscrowError::OrdersDoNotCross.into()
+        );
+    }
+
+    #[test]
+    fn quote_match_applies_both_mints_decimals() {
+        // X sells A (9 decimals) at 1.5 B (6 decimals), Y sells B at 0.5 A
+        let x = escrow(u64::MAX, u64::MAX, (3, 2), &[]);
+        let y = escrow(u64::MAX, u64::MAX, (1, 2), &[]);
+
+        assert_eq!(
+            quote_match(&x, &y, 1_000_000_000, 1_500_000, 9, 6).unwrap(),
+            MatchQuote {
+                owed_a_to_y: 750_000_000,
+                owed_b_to_x: 1_500_000,
+                spread_a: 250_000_000,
+                spread_b: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn fill_receipt_accumulates_fills() {
+        let mut receipt = FillReceipt {
+            escrow: Pubkey::default(),
+            escrow_created_slot: 0,
+            taker: Pubkey::default(),
+            maker: Pubkey::default(),
+            mint_a: Pubkey::default(),
+            mint_b: Pubkey::default(),
+            amount_a_received: 0,
+            amount_b_paid: 0,
+            fill_count: 0,
+            last_fill_at: 0,
+            bump: 255,
+        };
+
+        receipt.record(100, 250, 1_000).unwrap();
+        receipt.record(40, 100, 1_500).unwrap();
+
+        assert_eq!(receipt.amount_a_received, 140);
+        assert_eq!(receipt.amount_b_paid, 350);
+        assert_eq!(receipt.fill_count, 2);
+        assert_eq!(receipt.last_fill_at, 1_500);
+
+        // an overflowing fill is rejected
+        assert_eq!(
+            receipt.record(u64::MAX, 0, 2_000).unwrap_err(),
+            EscrowError::Overflow.into()
+        );
+    }
+}
@@ -0,0 +1,337 @@
+// keeper: scans the time-based-escrow program for escrows whose expiry has passed
+// and calls `crank_refund` on each one, sending the tokens back to the maker and
+// collecting the bounty the maker reserved at `make`
+
+use std::{error::Error, rc::Rc, str::FromStr, thread, time::Duration};
+
+use anchor_client::{
+    anchor_lang::system_program,
+    solana_client::{
+        client_error::{ClientError as RpcClientError, ClientErrorKind},
+        rpc_request::RpcError,
+    },
+    solana_sdk::{
+        commitment_config::CommitmentConfig,
+        compute_budget::ComputeBudgetInstruction,
+        pubkey::Pubkey,
+        signature::{read_keypair_file, Keypair},
+        sysvar,
+        transaction::TransactionError,
+    },
+    Client, ClientError, Cluster, Program,
+};
+use anchor_spl::associated_token::{self, get_associated_token_address_with_program_id};
+use clap::Parser;
+use time_based_escrow::{accounts, instruction, DeadlineKind, Escrow};
+
+type KeeperResult<T> = Result<T, Box<dyn Error>>;
+
+#[derive(Parser, Debug)]
+#[command(name = "keeper", about = "Cranks expired time-based escrows back to their makers")]
+struct Args {
+    /// cluster to run against: localnet, devnet, mainnet or an rpc url
+    #[arg(long, default_value = "localnet")]
+    cluster: String,
+
+    /// keypair that signs and pays for the crank transactions, it receives the bounties
+    #[arg(long, default_value = "~/.config/solana/id.json")]
+    keypair: String,
+
+    /// program id of the deployed time-based-escrow program
+    #[arg(long, default_value_t = time_based_escrow::ID)]
+    program_id: Pubkey,
+
+    /// priority fee in micro-lamports per compute unit, 0 leaves it unset
+    #[arg(long, default_value_t = 0)]
+    compute_unit_price: u64,
+
+    /// compute unit limit requested for each crank, 0 leaves the default
+    #[arg(long, default_value_t = 0)]
+    compute_unit_limit: u32,
+
+    /// skip escrows whose bounty is below this many lamports
+    #[arg(long, default_value_t = 0)]
+    min_bounty: u64,
+
+    /// how many times a crank that hit a network or expired blockhash error is retried before moving on
+    #[arg(long, default_value_t = 3)]
+    max_retries: u32,
+
+    /// seconds to wait between retries, doubled after every failure
+    #[arg(long, default_value_t = 2)]
+    retry_delay: u64,
+
+    /// seconds between scans, 0 runs a single scan and exits
+    #[arg(long, default_value_t = 0)]
+    interval: u64,
+
+    /// simulate the crank transactions instead of sending them
+    #[arg(long)]
+    dry_run: bool,
+}
+
+fn main() -> KeeperResult<()> {
+    let args = Args::parse();
+
+    let keypair = read_keypair_file(expand_home(&args.keypair))
+        .map_err(|err| format!("failed to read keypair {}: {err}", args.keypair))?;
+    let cluster = Cluster::from_str(&args.cluster)?;
+    let client = Client::new_with_options(cluster, Rc::new(keypair), CommitmentConfig::confirmed());
+    let program = client.program(args.program_id)?;
+
+    loop {
+        if let Err(err) = scan_and_crank(&program, &args) {
+            eprintln!("scan failed: {err}");
+        }
+
+        if args.interval == 0 {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_secs(args.interval));
+    }
+}
+
+// one pass over every escrow account owned by the program
+fn scan_and_crank(program: &Program<Rc<Keypair>>, args: &Args) -> KeeperResult<()> {
+    let (slot, unix_timestamp) = cluster_clock(program)?;
+    let escrows = program.accounts::<Escrow>(vec![])?;
+
+    // each escrow is judged against the clock its deadlines are measured in
+    let expired: Vec<(Pubkey, Escrow)> = escrows
+        .into_iter()
+        .filter(|(_, escrow)| {
+            let now = match escrow.deadline_kind {
+                DeadlineKind::UnixTimestamp => unix_timestamp,
+                DeadlineKind::Slot => slot,
+            };
+            now >= escrow.expires_at && escrow.bounty >= args.min_bounty
+        })
+        .collect();
+
+    println!(
+        "found {} expired escrow(s) at slot {slot}, unix timestamp {unix_timestamp}",
+        expired.len()
+    );
+
+    for (address, escrow) in expired {
+        if let Err(err) = crank_with_retries(program, args, &address, &escrow) {
+            eprintln!("giving up on {address}: {err}");
+        }
+    }
+
+    Ok(())
+}
+
+fn crank_with_retries(
+    program: &Program<Rc<Keypair>>,
+    args: &Args,
+    address: &Pubkey,
+    escrow: &Escrow,
+) -> KeeperResult<()> {
+    let mut delay = Duration::from_secs(args.retry_delay);
+    let mut attempt = 0;
+
+    loop {
+        match crank(program, args, address, escrow) {
+            Ok(()) => return Ok(()),
+            Err(err) if attempt < args.max_retries && is_transient(err.as_ref()) => {
+                attempt += 1;
+                eprintln!("crank of {address} failed (attempt {attempt}/{}): {err}", args.max_retries);
+                thread::sleep(delay);
+                delay *= 2;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+// only failures a later attempt can get past are retried: the rpc being unreachable, or the
+// blockhash expiring before the transaction landed. Program errors (already refunded, not
+// expired yet, ...) fail the same way every time, so they give up straight away
+fn is_transient(err: &(dyn Error + 'static)) -> bool {
+    let rpc_err = match err.downcast_ref::<ClientError>() {
+        Some(ClientError::SolanaClientError(rpc_err)) => rpc_err,
+        Some(_) => return false,
+        None => match err.downcast_ref::<RpcClientError>() {
+            Some(rpc_err) => rpc_err,
+            None => return false,
+        },
+    };
+
+    if rpc_err.get_transaction_error() == Some(TransactionError::BlockhashNotFound) {
+        return true;
+    }
+
+    match rpc_err.kind() {
+        ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) => true,
+        ClientErrorKind::RpcError(RpcError::RpcRequestError(_)) => true,
+        // send_and_confirm gives up with this once the blockhash has expired unconfirmed
+        ClientErrorKind::RpcError(RpcError::ForUser(message)) => {
+            message.starts_with("unable to confirm transaction")
+        }
+        _ => false,
+    }
+}
+
+// builds the crank_refund transaction for one escrow and sends or simulates it
+fn crank(
+    program: &Program<Rc<Keypair>>,
+    args: &Args,
+    address: &Pubkey,
+    escrow: &Escrow,
+) -> KeeperResult<()> {
+    // the mint's owner tells us which token program (token or token-2022) the vault lives under
+    let rpc = program.rpc();
+    let token_program = rpc.get_account(&escrow.mint_a)?.owner;
+
+    let mut request = program.request();
+
+    if args.compute_unit_limit > 0 {
+        request = request.instruction(ComputeBudgetInstruction::set_compute_unit_limit(
+            args.compute_unit_limit,
+        ));
+    }
+    if args.compute_unit_price > 0 {
+        request = request.instruction(ComputeBudgetInstruction::set_compute_unit_price(
+            args.compute_unit_price,
+        ));
+    }
+
+    let request = request
+        .accounts(accounts::CrankRefund {
+            cranker: program.payer(),
+            maker: escrow.maker,
+            mint_a: escrow.mint_a,
+            maker_ata_a: get_associated_token_address_with_program_id(
+                &escrow.maker,
+                &escrow.mint_a,
+                &token_program,
+            ),
+            escrow: *address,
+            vault: get_associated_token_address_with_program_id(
+                address,
+                &escrow.mint_a,
+                &token_program,
+            ),
+            associated_token_program: associated_token::ID,
+            token_program,
+            system_program: system_program::ID,
+        })
+        .args(instruction::CrankRefund {});
+
+    if args.dry_run {
+        let transaction = request.signed_transaction()?;
+        let simulation = rpc.simulate_transaction(&transaction)?.value;
+        match simulation.err {
+            Some(err) => println!("[dry-run] {address} would fail: {err}"),
+            None => println!(
+                "[dry-run] {address} would be refunded to {} for a {} lamport bounty",
+                escrow.maker, escrow.bounty
+            ),
+        }
+        return Ok(());
+    }
+
+    let signature = request.send()?;
+    println!(
+        "cranked {address} back to {} (bounty {} lamports): {signature}",
+        escrow.maker, escrow.bounty
+    );
+    Ok(())
+}
+
+// reads slot and unix_timestamp straight out of the clock sysvar so expiry is judged by cluster time, not ours
+// the clock is five little-endian 8 byte fields: slot, epoch_start_timestamp, epoch, leader_schedule_epoch, unix_timestamp
+fn cluster_clock(program: &Program<Rc<Keypair>>) -> KeeperResult<(i64, i64)> {
+    let clock = program.rpc().get_account_data(&sysvar::clock::ID)?;
+    let field = |offset: usize| -> KeeperResult<i64> {
+        let bytes = clock
+            .get(offset..offset + 8)
+            .ok_or("clock sysvar data is too short")?;
+        Ok(i64::from_le_bytes(bytes.try_into()?))
+    };
+    Ok((field(0)?, field(32)?))
+}
+
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{home}/{rest}"),
+        _ => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_client::solana_client::{
+        rpc_request::RpcResponseErrorData, rpc_response::RpcSimulateTransactionResult,
+    };
+    use anchor_client::solana_sdk::instruction::InstructionError;
+
+    fn preflight_failure(err: TransactionError) -> RpcClientError {
+        let simulation = RpcSimulateTransactionResult {
+            err: Some(err),
+            logs: None,
+            accounts: None,
+            units_consumed: None,
+            loaded_accounts_data_size: None,
+            return_data: None,
+            inner_instructions: None,
+            replacement_blockhash: None,
+        };
+        ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            code: -32002,
+            message: "Transaction simulation failed".to_string(),
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(simulation),
+        })
+        .into()
+    }
+
+    fn sent(err: RpcClientError) -> Box<dyn Error> {
+        Box::new(ClientError::SolanaClientError(err))
+    }
+
+    #[test]
+    fn transport_errors_are_retried() {
+        let io: RpcClientError =
+            ClientErrorKind::Io(std::io::ErrorKind::ConnectionRefused.into()).into();
+        assert!(is_transient(sent(io).as_ref()));
+
+        let request: RpcClientError =
+            ClientErrorKind::RpcError(RpcError::RpcRequestError("timed out".to_string())).into();
+        assert!(is_transient(sent(request).as_ref()));
+
+        // errors from plain rpc calls come through without the anchor wrapper
+        let io: RpcClientError =
+            ClientErrorKind::Io(std::io::ErrorKind::TimedOut.into()).into();
+        assert!(is_transient(&io));
+    }
+
+    #[test]
+    fn expired_blockhash_is_retried() {
+        let stale = preflight_failure(TransactionError::BlockhashNotFound);
+        assert!(is_transient(sent(stale).as_ref()));
+
+        let unconfirmed: RpcClientError = ClientErrorKind::RpcError(RpcError::ForUser(
+            "unable to confirm transaction. This can happen in situations such as transaction expiration and insufficient fee-payer funds".to_string(),
+        ))
+        .into();
+        assert!(is_transient(sent(unconfirmed).as_ref()));
+    }
+
+    #[test]
+    fn program_errors_are_not_retried() {
+        // EscrowNotExpired is the first custom error, anchor numbers them from 6000
+        let not_expired = preflight_failure(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(6000),
+        ));
+        assert!(!is_transient(sent(not_expired).as_ref()));
+
+        let already_refunded = preflight_failure(TransactionError::AccountNotFound);
+        assert!(!is_transient(sent(already_refunded).as_ref()));
+
+        assert!(!is_transient(Box::<dyn Error>::from("clock sysvar data is too short").as_ref()));
+        assert!(!is_transient(&ClientError::AccountNotFound));
+    }
+}
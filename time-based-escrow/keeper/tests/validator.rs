@@ -0,0 +1,180 @@
+// end to end run of the keeper binary against a local validator with the program deployed:
+//
+//   anchor build
+//   solana-test-validator --reset --bpf-program J1e4TfaFKrYvNM1EeyM1Pnh1XggW6HgFz9bFdNWuwcX3 target/deploy/time_based_escrow.so
+//   cargo test -p time-based-escrow-keeper -- --ignored
+//
+// the escrow is made with a slot deadline a few slots out, so it expires a couple of seconds after `make`
+
+use std::{process::Command, rc::Rc, thread, time::Duration};
+
+use anchor_client::{
+    anchor_lang::system_program,
+    solana_client::rpc_client::RpcClient,
+    solana_sdk::{
+        commitment_config::CommitmentConfig,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        signature::{write_keypair_file, Keypair, Signer},
+        transaction::Transaction,
+    },
+    Client, Cluster,
+};
+use anchor_spl::{
+    associated_token::{
+        self, get_associated_token_address,
+        spl_associated_token_account::instruction::create_associated_token_account,
+    },
+    token::{self, spl_token},
+};
+use solana_system_interface::instruction as system_instruction;
+use time_based_escrow::{accounts, instruction, DeadlineKind, Escrow, Expiry};
+
+const RPC_URL: &str = "http://127.0.0.1:8899";
+const DEPOSIT: u64 = 1_000;
+const BOUNTY: u64 = 1_000_000;
+const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+#[test]
+#[ignore = "needs a local validator with time-based-escrow deployed"]
+fn keeper_cranks_an_expired_escrow_back_to_the_maker() {
+    let rpc = RpcClient::new_with_commitment(RPC_URL.to_string(), CommitmentConfig::confirmed());
+    let maker = Keypair::new();
+    let keeper = Keypair::new();
+    fund(&rpc, &maker.pubkey());
+    fund(&rpc, &keeper.pubkey());
+
+    // two fresh mints, with the maker holding the deposit in token A
+    let mint_a = Keypair::new();
+    let mint_b = Keypair::new();
+    let maker_ata_a = get_associated_token_address(&maker.pubkey(), &mint_a.pubkey());
+    let mut setup = create_mint(&rpc, &maker.pubkey(), &mint_a.pubkey());
+    setup.extend(create_mint(&rpc, &maker.pubkey(), &mint_b.pubkey()));
+    setup.push(create_associated_token_account(
+        &maker.pubkey(),
+        &maker.pubkey(),
+        &mint_a.pubkey(),
+        &token::ID,
+    ));
+    setup.push(
+        spl_token::instruction::mint_to(
+            &token::ID,
+            &mint_a.pubkey(),
+            &maker_ata_a,
+            &maker.pubkey(),
+            &[],
+            DEPOSIT,
+        )
+        .unwrap(),
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &setup,
+        Some(&maker.pubkey()),
+        &[&maker, &mint_a, &mint_b],
+        rpc.get_latest_blockhash().unwrap(),
+    );
+    rpc.send_and_confirm_transaction(&transaction).unwrap();
+
+    // an escrow expiring a few slots from now, carrying a bounty for whoever cranks it
+    let seed = 7u64;
+    let (escrow, _) = Pubkey::find_program_address(
+        &[b"escrow", maker.pubkey().as_ref(), &seed.to_le_bytes()],
+        &time_based_escrow::ID,
+    );
+    let maker_pubkey = maker.pubkey();
+    let client = Client::new_with_options(
+        Cluster::Localnet,
+        Rc::new(maker),
+        CommitmentConfig::confirmed(),
+    );
+    let program = client.program(time_based_escrow::ID).unwrap();
+    program
+        .request()
+        .accounts(accounts::Make {
+            maker: maker_pubkey,
+            mint_a: mint_a.pubkey(),
+            mint_b: mint_b.pubkey(),
+            maker_ata_a,
+            escrow,
+            vault: get_associated_token_address(&escrow, &mint_a.pubkey()),
+            associated_token_program: associated_token::ID,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        })
+        .args(instruction::Make {
+            seed,
+            deposit: DEPOSIT,
+            receive: 500,
+            deadline_kind: DeadlineKind::Slot,
+            starts_at: None,
+            expiry: Expiry::In(4),
+            operator: None,
+            bounty: BOUNTY,
+        })
+        .send()
+        .unwrap();
+    assert_eq!(token_balance(&rpc, &maker_ata_a), 0);
+
+    let expires_at = program.account::<Escrow>(escrow).unwrap().expires_at;
+    while (rpc.get_slot().unwrap() as i64) <= expires_at {
+        thread::sleep(Duration::from_millis(400));
+    }
+
+    // a single scan with the keeper's own keypair, it should find and crank the escrow
+    let keypair_path = std::env::temp_dir().join(format!("keeper-{}.json", keeper.pubkey()));
+    write_keypair_file(&keeper, &keypair_path).unwrap();
+    let keeper_before = rpc.get_balance(&keeper.pubkey()).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_keeper"))
+        .args(["--cluster", RPC_URL, "--keypair"])
+        .arg(&keypair_path)
+        .status()
+        .unwrap();
+    std::fs::remove_file(&keypair_path).ok();
+    assert!(status.success());
+
+    // the tokens are back with the maker, the escrow is closed and the keeper was paid
+    assert_eq!(token_balance(&rpc, &maker_ata_a), DEPOSIT);
+    assert!(rpc
+        .get_account_with_commitment(&escrow, CommitmentConfig::confirmed())
+        .unwrap()
+        .value
+        .is_none());
+    let keeper_after = rpc.get_balance(&keeper.pubkey()).unwrap();
+    assert!(keeper_after > keeper_before + BOUNTY / 2);
+}
+
+fn fund(rpc: &RpcClient, pubkey: &Pubkey) {
+    let signature = rpc.request_airdrop(pubkey, 10 * LAMPORTS_PER_SOL).unwrap();
+    while !rpc.confirm_transaction(&signature).unwrap() {
+        thread::sleep(Duration::from_millis(200));
+    }
+}
+
+fn create_mint(
+    rpc: &RpcClient,
+    authority: &Pubkey,
+    mint: &Pubkey,
+) -> Vec<anchor_client::solana_sdk::instruction::Instruction> {
+    let rent = rpc
+        .get_minimum_balance_for_rent_exemption(spl_token::state::Mint::LEN)
+        .unwrap();
+    vec![
+        system_instruction::create_account(
+            authority,
+            mint,
+            rent,
+            spl_token::state::Mint::LEN as u64,
+            &token::ID,
+        ),
+        spl_token::instruction::initialize_mint2(&token::ID, mint, authority, None, 6).unwrap(),
+    ]
+}
+
+fn token_balance(rpc: &RpcClient, account: &Pubkey) -> u64 {
+    rpc.get_token_account_balance(account)
+        .unwrap()
+        .amount
+        .parse()
+        .unwrap()
+}
@@ -0,0 +1,11 @@
+// constants.rs: limits enforced by the program
+
+// the furthest in the future an escrow can expire, counted from the moment it is made (365 days in seconds)
+pub const MAX_EXPIRY_DURATION: i64 = 365 * 24 * 60 * 60;
+
+// when the expiry is shortened it can not land sooner than this after now, so takers preparing a fill have time (1 hour in seconds)
+pub const MIN_EXPIRY_NOTICE: i64 = 60 * 60;
+
+// the same two limits for escrows whose deadlines are counted in slots, assuming roughly 400ms slots
+pub const MAX_EXPIRY_SLOTS: i64 = MAX_EXPIRY_DURATION * 5 / 2;
+pub const MIN_EXPIRY_NOTICE_SLOTS: i64 = MIN_EXPIRY_NOTICE * 5 / 2;
@@ -0,0 +1,25 @@
+// events.rs: events emitted by the program so indexers don't have to decode account data
+
+use anchor_lang::prelude::*;
+
+use crate::DeadlineKind;
+
+#[event]
+pub struct EscrowCreated {
+    pub escrow: Pubkey,    // address of the new escrow
+    pub maker: Pubkey,     // pubkey of escrow creator
+    pub mint_a: Pubkey,    // token being deposited
+    pub mint_b: Pubkey,    // token expected in return
+    pub deposit: u64,      // amount of token A deposited
+    pub receive: u64,      // amount of token B to receive
+    pub deadline_kind: DeadlineKind,  // whether the deadlines below are unix timestamps or slots
+    pub starts_at: i64,    // deadline when the escrow opens for takers
+    pub expires_at: i64,   // resolved deadline when the escrow expires
+}
+
+#[event]
+pub struct ExpiryUpdated {
+    pub escrow: Pubkey,        // address of the escrow
+    pub old_expires_at: i64,   // expiry before the update
+    pub new_expires_at: i64,   // resolved expiry after the update
+}
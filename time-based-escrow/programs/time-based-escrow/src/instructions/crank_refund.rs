@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::Escrow;
+use crate::contexts::errors::EscrowError;
+use crate::contexts::refund::refund_and_close_vault;
+
+#[derive(Accounts)]
+pub struct CrankRefund<'info> {
+
+    // anyone can crank an expired escrow, they collect the bounty
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    // maker's pubkey, receives the refund and the rent
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    // mint of token A
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    // maker's token A ATA, recreated at the cranker's cost if the maker closed it
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // escrow account, what is left after the bounty goes back to the maker
+    #[account(
+        mut,
+        close = maker,
+        has_one = mint_a,
+        has_one = maker,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    // vault account
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    // associated token program
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    // token program
+    pub token_program: Interface<'info, TokenInterface>,
+
+    // system program
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CrankRefund<'info> {
+    pub fn refund_and_close_vault(&mut self) -> Result<()> {
+
+        // the crank only works once the escrow has expired
+        let clock = Clock::get()?;
+        require!(
+            self.escrow.now(&clock) >= self.escrow.expires_at,
+            EscrowError::EscrowNotExpired
+        );
+
+        refund_and_close_vault(
+            &self.escrow,
+            &self.vault,
+            &self.mint_a,
+            &self.maker_ata_a,
+            self.maker.to_account_info(),
+            &self.token_program,
+        )
+    }
+
+    // paying the bounty out of the escrow's lamports, the escrow is program owned so we move them directly
+    pub fn pay_bounty(&mut self) -> Result<()> {
+        let bounty = self.escrow.bounty;
+        self.escrow.sub_lamports(bounty)?;
+        self.cranker.add_lamports(bounty)?;
+        Ok(())
+    }
+}
@@ -6,4 +6,18 @@ pub enum EscrowError {
     EscrowNotExpired,
     #[msg("The escrow has expired.")]
     EscrowExpired,
+    #[msg("Only the maker or the operator can manage this escrow.")]
+    Unauthorized,
+    #[msg("The expiry must be in the future.")]
+    ExpiryInPast,
+    #[msg("The expiry is further out than the maximum allowed.")]
+    ExpiryTooFar,
+    #[msg("The expiry overflows.")]
+    ExpiryOverflow,
+    #[msg("The escrow has not started yet.")]
+    EscrowNotStarted,
+    #[msg("The start time must be before the expiry.")]
+    InvalidStartTime,
+    #[msg("The expiry can not be shortened to less than the minimum notice period from now.")]
+    ExpiryNoticeTooShort,
 } 
\ No newline at end of file
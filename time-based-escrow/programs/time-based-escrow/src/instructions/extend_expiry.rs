@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+use crate::{Escrow, Expiry, ExpiryUpdated};
+use crate::contexts::errors::EscrowError;
+
+#[derive(Accounts)]
+pub struct ExtendExpiry<'info> {
+
+    // maker or operator changing the expiry
+    pub authority: Signer<'info>,
+
+    // maker's pubkey, needed to derive the escrow
+    pub maker: SystemAccount<'info>,
+
+    // escrow account
+    #[account(
+        mut,
+        has_one = maker,
+        constraint = escrow.is_maker_or_operator(&authority.key()) @ EscrowError::Unauthorized,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+}
+
+impl<'info> ExtendExpiry<'info> {
+
+    // moving the expiry of a live escrow without refunding and making it again
+    pub fn extend_expiry(&mut self, expiry: Expiry) -> Result<()> {
+        let now = self.escrow.now(&Clock::get()?);
+        let old_expires_at = self.escrow.expires_at;
+        let new_expires_at = self.escrow.extended_expiry(expiry, now)?;
+
+        self.escrow.expires_at = new_expires_at;
+
+        emit!(ExpiryUpdated {
+            escrow: self.escrow.key(),
+            old_expires_at,
+            new_expires_at,
+        });
+
+        Ok(())
+    }
+}
@@ -1,11 +1,13 @@
 use anchor_lang::prelude::*;
 
+use anchor_lang::system_program::{transfer, Transfer};
+
 use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
-use crate::Escrow;
+use crate::{resolve_window, DeadlineKind, Escrow, EscrowCreated, Expiry};
 
 #[derive(Accounts)]
 #[instruction(seed: u64)]
@@ -70,15 +72,22 @@ pub struct Make<'info> {
 impl<'info> Make<'info> {
 
     // creating a new escrow
+    #[allow(clippy::too_many_arguments)]
     pub fn save_escrow(
         &mut self,
         seed: u64,
         receive: u64,
-        duration: i64,
+        deadline_kind: DeadlineKind,
+        starts_at: Option<i64>,
+        expiry: Expiry,
+        operator: Option<Pubkey>,
+        bounty: u64,
         bumps: &MakeBumps,
     ) -> Result<()> {
-        let clock = Clock::get()?;                           // get the current timestamp from the Solana clock sysvar
-        let expires_at = clock.unix_timestamp + duration;    // set the expiry by adding duration in seconds
+        let clock = Clock::get()?;                           // get the current time from the Solana clock sysvar
+        let now = deadline_kind.now(&clock);                 // unix timestamp or slot, depending on the deadline kind
+        let (starts_at, expires_at) =                        // absolute or relative expiry, validated against now
+            resolve_window(deadline_kind, starts_at, expiry, now)?;   // and a start time that must come before it
 
         self.escrow.set_inner(Escrow {                       // create a new escrow using set_inner
             seed,                                             // seed from which PDA is derived
@@ -86,13 +95,33 @@ impl<'info> Make<'info> {
             mint_a: self.mint_a.key(),                        // token being deposited
             mint_b: self.mint_b.key(),                        // token expected in return
             receive,                                          // amount of token B to receive
-            expires_at,                                       // unix timestamp when escrow expires
+            deadline_kind,                                    // whether the deadlines are unix timestamps or slots
+            starts_at,                                        // deadline when the escrow opens for takers
+            expires_at,                                       // deadline when escrow expires
+            operator,                                         // optional key allowed to refund on the maker's behalf
+            bounty,                                           // lamports paid to the keeper that cranks the refund
             bump: bumps.escrow,                               // bump of escrow PDA
         });
 
         Ok(())
     }
 
+    // announcing the new escrow with its resolved expiry
+    pub fn emit_created(&self, deposit: u64) -> Result<()> {
+        emit!(EscrowCreated {
+            escrow: self.escrow.key(),
+            maker: self.maker.key(),
+            mint_a: self.mint_a.key(),
+            mint_b: self.mint_b.key(),
+            deposit,
+            receive: self.escrow.receive,
+            deadline_kind: self.escrow.deadline_kind,
+            starts_at: self.escrow.starts_at,
+            expires_at: self.escrow.expires_at,
+        });
+        Ok(())
+    }
+
     // depositing token A into the vault
     pub fn deposit(&mut self, deposit: u64) -> Result<()> {
         let transfer_accounts = TransferChecked {
@@ -106,4 +135,20 @@ impl<'info> Make<'info> {
 
         transfer_checked(cpi_ctx, deposit, self.mint_a.decimals)
     }
+
+    // moving the keeper bounty from the maker into the escrow account, on top of its rent
+    pub fn fund_bounty(&mut self, bounty: u64) -> Result<()> {
+        if bounty == 0 {
+            return Ok(());
+        }
+
+        let transfer_accounts = Transfer {
+            from: self.maker.to_account_info(),             // source: maker's wallet
+            to: self.escrow.to_account_info(),              // destination: escrow PDA
+        };
+
+        let cpi_ctx = CpiContext::new(self.system_program.to_account_info(), transfer_accounts);
+
+        transfer(cpi_ctx, bounty)
+    }
 }
@@ -10,4 +10,13 @@ pub mod refund;
 pub use refund::*;
 
 pub mod take;
-pub use take::*;
\ No newline at end of file
+pub use take::*;
+
+pub mod set_operator;
+pub use set_operator::*;
+
+pub mod crank_refund;
+pub use crank_refund::*;
+
+pub mod extend_expiry;
+pub use extend_expiry::*;
\ No newline at end of file
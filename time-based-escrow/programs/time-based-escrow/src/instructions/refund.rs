@@ -14,9 +14,12 @@ use crate::contexts::errors::EscrowError;
 #[derive(Accounts)]
 pub struct Refund<'info> {
 
-    // maker's pubkey
+    // maker or operator signing the refund
+    authority: Signer<'info>,
+
+    // maker's pubkey, the refund and the rent always go to the maker
     #[account(mut)]
-    maker: Signer<'info>,
+    maker: SystemAccount<'info>,
 
     // mint of token A
     mint_a: InterfaceAccount<'info, Mint>,
@@ -36,6 +39,7 @@ pub struct Refund<'info> {
         close = maker,
         has_one = mint_a,
         has_one = maker,
+        constraint = escrow.is_maker_or_operator(&authority.key()) @ EscrowError::Unauthorized,
         seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
         bump = escrow.bump
     )]
@@ -66,51 +70,70 @@ impl<'info> Refund<'info> {
         // ensure current time is after escrow expiration
         let clock = Clock::get()?;
         require!(
-            clock.unix_timestamp >= self.escrow.expires_at,
+            self.escrow.now(&clock) >= self.escrow.expires_at,
             EscrowError::EscrowNotExpired
         );
 
-        // setting up the seeds for the escrow account
-        let maker_key = self.maker.key();
-        let signer_seeds: [&[&[u8]]; 1] = [&[
-            b"escrow",
-            maker_key.as_ref(),
-            &self.escrow.seed.to_le_bytes()[..],
-            &[self.escrow.bump],
-        ]];
-
-        // refunding token A back to the maker
-        let transfer_accounts = TransferChecked {
-            from: self.vault.to_account_info(),
-            mint: self.mint_a.to_account_info(),
-            to: self.maker_ata_a.to_account_info(),
-            authority: self.escrow.to_account_info(),
-        };
-
-        let cpi_ctx = CpiContext::new_with_signer(
-            self.token_program.to_account_info(),
-            transfer_accounts,
-            &signer_seeds,
-        );
-
-        transfer_checked(cpi_ctx, self.vault.amount, self.mint_a.decimals)?;
-
-
-        // closing the vault account
-        let close_accounts = CloseAccount {
-            account: self.vault.to_account_info(),
-            destination: self.maker.to_account_info(),
-            authority: self.escrow.to_account_info(),
-        };
-
-        let ctx = CpiContext::new_with_signer(
-            self.token_program.to_account_info(),
-            close_accounts,
-            &signer_seeds,
-        );
-
-        close_account(ctx)
+        refund_and_close_vault(
+            &self.escrow,
+            &self.vault,
+            &self.mint_a,
+            &self.maker_ata_a,
+            self.maker.to_account_info(),
+            &self.token_program,
+        )
     }
 }
 
+// draining the vault back to the maker and closing it, shared by the refund and the crank
+pub fn refund_and_close_vault<'info>(
+    escrow: &Account<'info, Escrow>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    mint_a: &InterfaceAccount<'info, Mint>,
+    maker_ata_a: &InterfaceAccount<'info, TokenAccount>,
+    maker: AccountInfo<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<()> {
+
+    // setting up the seeds for the escrow account
+    let signer_seeds: [&[&[u8]]; 1] = [&[
+        b"escrow",
+        escrow.maker.as_ref(),
+        &escrow.seed.to_le_bytes()[..],
+        &[escrow.bump],
+    ]];
+
+    // refunding token A back to the maker
+    let transfer_accounts = TransferChecked {
+        from: vault.to_account_info(),
+        mint: mint_a.to_account_info(),
+        to: maker_ata_a.to_account_info(),
+        authority: escrow.to_account_info(),
+    };
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        transfer_accounts,
+        &signer_seeds,
+    );
+
+    transfer_checked(cpi_ctx, vault.amount, mint_a.decimals)?;
+
+
+    // closing the vault account, the rent goes to the maker
+    let close_accounts = CloseAccount {
+        account: vault.to_account_info(),
+        destination: maker,
+        authority: escrow.to_account_info(),
+    };
+
+    let ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        close_accounts,
+        &signer_seeds,
+    );
+
+    close_account(ctx)
+}
+
 
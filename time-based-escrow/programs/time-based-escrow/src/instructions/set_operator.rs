@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::Escrow;
+
+#[derive(Accounts)]
+pub struct SetOperator<'info> {
+
+    // maker's pubkey, only the maker can change who operates the escrow
+    pub maker: Signer<'info>,
+
+    // escrow account
+    #[account(
+        mut,
+        has_one = maker,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+}
+
+impl<'info> SetOperator<'info> {
+
+    // replacing the operator, passing None removes it
+    pub fn set_operator(&mut self, operator: Option<Pubkey>) -> Result<()> {
+        self.escrow.operator = operator;
+        Ok(())
+    }
+}
@@ -9,7 +9,6 @@ use anchor_spl::{
 };
 
 use crate::Escrow;
-use crate::contexts::errors::EscrowError;
 
 #[derive(Accounts)]
 pub struct Take<'info> {
@@ -93,12 +92,8 @@ impl<'info> Take<'info> {
     // transferring token B from taker to maker
     pub fn deposit(&mut self) -> Result<()> {
 
-        // check if escrow has expired
-        let clock = Clock::get()?;
-        require!(
-            clock.unix_timestamp < self.escrow.expires_at,
-            EscrowError::EscrowExpired
-        );
+        // check the escrow has started and has not expired
+        self.escrow.check_open(&Clock::get()?)?;
 
         // step 1: define all the accounts using the TransferChecked method
         let transfer_accounts = TransferChecked {
@@ -160,5 +155,3 @@ impl<'info> Take<'info> {
         close_account(ctx)
     }
 }
-w
-
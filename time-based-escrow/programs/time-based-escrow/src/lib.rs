@@ -1,21 +1,50 @@
 use anchor_lang::prelude::*;
 
+#[path = "instructions/mod.rs"]
 pub mod contexts;
 use contexts::*;
 
 pub mod state;
 pub use state::*;
 
+pub mod constants;
+pub use constants::*;
+
+pub mod events;
+pub use events::*;
+
 declare_id!("J1e4TfaFKrYvNM1EeyM1Pnh1XggW6HgFz9bFdNWuwcX3");
 
 #[program]
 pub mod time_based_escrow {
     use super::*;
 
-    // creates a new escrow
-    pub fn make(ctx: Context<Make>, seed: u64, deposit: u64, receive: u64, expires_at: i64) -> Result<()> {
+    // creates a new escrow, takeable from `starts_at` (or right away) until the expiry, both measured in unix seconds or slots
+    #[allow(clippy::too_many_arguments)]
+    pub fn make(
+        ctx: Context<Make>,
+        seed: u64,
+        deposit: u64,
+        receive: u64,
+        deadline_kind: DeadlineKind,
+        starts_at: Option<i64>,
+        expiry: Expiry,
+        operator: Option<Pubkey>,
+        bounty: u64,
+    ) -> Result<()> {
         ctx.accounts.deposit(deposit)?;
-        ctx.accounts.save_escrow(seed, receive, expires_at, &ctx.bumps)
+        ctx.accounts.fund_bounty(bounty)?;
+        ctx.accounts.save_escrow(
+            seed,
+            receive,
+            deadline_kind,
+            starts_at,
+            expiry,
+            operator,
+            bounty,
+            &ctx.bumps,
+        )?;
+        ctx.accounts.emit_created(deposit)
     }
 
     // taker fulfills the swap
@@ -24,8 +53,24 @@ pub mod time_based_escrow {
         ctx.accounts.withdraw_and_close_vault()
     }
 
-    // maker gets refund after expiry
+    // maker gets refund after expiry, the operator can trigger it on the maker's behalf
     pub fn refund(ctx: Context<Refund>) -> Result<()> {
         ctx.accounts.refund_and_close_vault()
     }
+
+    // anyone refunds an expired escrow to the maker and collects the bounty
+    pub fn crank_refund(ctx: Context<CrankRefund>) -> Result<()> {
+        ctx.accounts.refund_and_close_vault()?;
+        ctx.accounts.pay_bounty()
+    }
+
+    // maker or operator moves the expiry of a live escrow
+    pub fn extend_expiry(ctx: Context<ExtendExpiry>, expiry: Expiry) -> Result<()> {
+        ctx.accounts.extend_expiry(expiry)
+    }
+
+    // maker sets, replaces or clears the operator
+    pub fn set_operator(ctx: Context<SetOperator>, operator: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.set_operator(operator)
+    }
 }
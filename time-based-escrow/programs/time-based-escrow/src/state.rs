@@ -2,6 +2,9 @@
 
 use anchor_lang::prelude::*;  // this is the prelude of the anchor lang, it contains the basic types and macros
 
+use crate::contexts::errors::EscrowError;
+use crate::{MAX_EXPIRY_DURATION, MAX_EXPIRY_SLOTS, MIN_EXPIRY_NOTICE, MIN_EXPIRY_NOTICE_SLOTS};
+
 #[account]                 // declares that this is going to be an account
 #[derive(InitSpace)]       // auto-calculates the space needed for the account 
 pub struct Escrow {        // this is the struct of the account, we are defining the fields of the account
@@ -10,6 +13,330 @@ pub struct Escrow {        // this is the struct of the account, we are defining
     pub mint_a: Pubkey,    // this is the mint of the token A
     pub mint_b: Pubkey,    // this is the mint of the token B
     pub receive: u64,      // this is the amount of token B that the maker is receiving
-    pub expires_at: i64,   // this is the expiry timestamp (unix seconds)
+    pub deadline_kind: DeadlineKind,  // this is the clock starts_at and expires_at are measured in
+    pub starts_at: i64,    // this is the deadline before which the escrow can not be taken
+    pub expires_at: i64,   // this is the expiry deadline
+    pub operator: Option<Pubkey>,  // this is an optional key allowed to manage the escrow on the maker's behalf
+    pub bounty: u64,       // this is the lamports the maker reserved to pay whoever cranks the refund after expiry
     pub bump: u8,          // this is the bump of the account
 }
+
+impl Escrow {
+    // the maker, or the operator if one is set, can manage the escrow
+    // funds always go back to the maker no matter who signs
+    pub fn is_maker_or_operator(&self, key: &Pubkey) -> bool {
+        self.maker == *key || self.operator == Some(*key)
+    }
+
+    // the current time in the same unit as this escrow's deadlines
+    pub fn now(&self, clock: &Clock) -> i64 {
+        self.deadline_kind.now(clock)
+    }
+
+    // takers are only let in from `starts_at` until just before `expires_at`
+    pub fn check_open(&self, clock: &Clock) -> Result<()> {
+        let now = self.now(clock);
+        require!(now >= self.starts_at, EscrowError::EscrowNotStarted);
+        require!(now < self.expires_at, EscrowError::EscrowExpired);
+        Ok(())
+    }
+
+    // the expiry an extend_expiry at `now` moves a live escrow to,
+    // shortening is allowed but never closer to now than the minimum notice period
+    pub fn extended_expiry(&self, expiry: Expiry, now: i64) -> Result<i64> {
+        let kind = self.deadline_kind;
+
+        // an expired escrow is left for the refund, it can not be revived
+        require!(now < self.expires_at, EscrowError::EscrowExpired);
+
+        let new_expires_at = expiry.resolve(kind, now)?;
+
+        if new_expires_at < self.expires_at {
+            let earliest = now
+                .checked_add(kind.min_notice())
+                .ok_or(EscrowError::ExpiryOverflow)?;
+            require!(new_expires_at >= earliest, EscrowError::ExpiryNoticeTooShort);
+        }
+
+        // the escrow must still open before it expires
+        require!(self.starts_at < new_expires_at, EscrowError::InvalidStartTime);
+
+        Ok(new_expires_at)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]   // chosen per escrow at `make`
+pub enum DeadlineKind {
+    UnixTimestamp,         // deadlines are unix seconds from `Clock::unix_timestamp`
+    Slot,                  // deadlines are slots from `Clock::slot`, deterministic for HTLC-style use
+}
+
+impl DeadlineKind {
+    // reading the matching field off the clock sysvar
+    pub fn now(self, clock: &Clock) -> i64 {
+        match self {
+            DeadlineKind::UnixTimestamp => clock.unix_timestamp,
+            DeadlineKind::Slot => clock.slot as i64,
+        }
+    }
+
+    // the furthest an expiry can be from now
+    pub fn max_expiry(self) -> i64 {
+        match self {
+            DeadlineKind::UnixTimestamp => MAX_EXPIRY_DURATION,
+            DeadlineKind::Slot => MAX_EXPIRY_SLOTS,
+        }
+    }
+
+    // the closest a shortened expiry can be to now
+    pub fn min_notice(self) -> i64 {
+        match self {
+            DeadlineKind::UnixTimestamp => MIN_EXPIRY_NOTICE,
+            DeadlineKind::Slot => MIN_EXPIRY_NOTICE_SLOTS,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]   // how the maker expresses the expiry at `make`
+pub enum Expiry {
+    At(i64),               // expires at this deadline (unix timestamp or slot, per the escrow's deadline kind)
+    In(i64),               // expires this many seconds or slots after now
+}
+
+impl Expiry {
+    // turning the expiry into an absolute deadline and checking it is in the future and within the maximum
+    pub fn resolve(self, kind: DeadlineKind, now: i64) -> Result<i64> {
+        let expires_at = match self {
+            Expiry::At(deadline) => deadline,
+            Expiry::In(duration) => now.checked_add(duration).ok_or(EscrowError::ExpiryOverflow)?,
+        };
+
+        require!(expires_at > now, EscrowError::ExpiryInPast);
+
+        let max_expires_at = now
+            .checked_add(kind.max_expiry())
+            .ok_or(EscrowError::ExpiryOverflow)?;
+        require!(expires_at <= max_expires_at, EscrowError::ExpiryTooFar);
+
+        Ok(expires_at)
+    }
+}
+
+// resolving the window a new escrow is open in, no start time means it is open right away
+pub fn resolve_window(
+    kind: DeadlineKind,
+    starts_at: Option<i64>,
+    expiry: Expiry,
+    now: i64,
+) -> Result<(i64, i64)> {
+    let expires_at = expiry.resolve(kind, now)?;
+    let starts_at = starts_at.unwrap_or(now);
+    require!(starts_at < expires_at, EscrowError::InvalidStartTime);
+    Ok((starts_at, expires_at))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: i64 = 1_700_000_000;
+
+    fn clock_at(slot: u64, unix_timestamp: i64) -> Clock {
+        Clock {
+            slot,
+            unix_timestamp,
+            ..Clock::default()
+        }
+    }
+
+    fn escrow(deadline_kind: DeadlineKind, starts_at: i64, expires_at: i64) -> Escrow {
+        Escrow {
+            seed: 0,
+            maker: Pubkey::new_unique(),
+            mint_a: Pubkey::new_unique(),
+            mint_b: Pubkey::new_unique(),
+            receive: 1,
+            deadline_kind,
+            starts_at,
+            expires_at,
+            operator: None,
+            bounty: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn take_window_follows_the_clock() {
+        let escrow = escrow(DeadlineKind::UnixTimestamp, START, START + 3_600);
+
+        assert_eq!(
+            escrow.check_open(&clock_at(0, START - 1)).unwrap_err(),
+            EscrowError::EscrowNotStarted.into()
+        );
+        assert!(escrow.check_open(&clock_at(0, START)).is_ok());
+        assert!(escrow.check_open(&clock_at(0, START + 3_599)).is_ok());
+        assert_eq!(
+            escrow.check_open(&clock_at(0, START + 3_600)).unwrap_err(),
+            EscrowError::EscrowExpired.into()
+        );
+    }
+
+    #[test]
+    fn slot_window_ignores_the_unix_timestamp() {
+        let escrow = escrow(DeadlineKind::Slot, 100, 200);
+
+        // a timestamp far past both deadlines changes nothing, only the slot counts
+        assert_eq!(
+            escrow.check_open(&clock_at(99, i64::MAX)).unwrap_err(),
+            EscrowError::EscrowNotStarted.into()
+        );
+        assert!(escrow.check_open(&clock_at(100, 0)).is_ok());
+        assert!(escrow.check_open(&clock_at(199, 0)).is_ok());
+        assert_eq!(
+            escrow.check_open(&clock_at(200, 0)).unwrap_err(),
+            EscrowError::EscrowExpired.into()
+        );
+    }
+
+    #[test]
+    fn window_must_start_before_it_expires() {
+        let kind = DeadlineKind::UnixTimestamp;
+
+        assert_eq!(
+            resolve_window(kind, None, Expiry::In(60), START).unwrap(),
+            (START, START + 60)
+        );
+        assert_eq!(
+            resolve_window(kind, Some(START + 30), Expiry::At(START + 60), START).unwrap(),
+            (START + 30, START + 60)
+        );
+        assert_eq!(
+            resolve_window(kind, Some(START + 60), Expiry::At(START + 60), START).unwrap_err(),
+            EscrowError::InvalidStartTime.into()
+        );
+        assert_eq!(
+            resolve_window(kind, Some(START + 61), Expiry::In(60), START).unwrap_err(),
+            EscrowError::InvalidStartTime.into()
+        );
+    }
+
+    const KINDS: [DeadlineKind; 2] = [DeadlineKind::UnixTimestamp, DeadlineKind::Slot];
+
+    #[test]
+    fn expiry_must_be_in_the_future() {
+        for kind in KINDS {
+            assert_eq!(Expiry::In(1).resolve(kind, START).unwrap(), START + 1);
+            assert_eq!(Expiry::At(START + 1).resolve(kind, START).unwrap(), START + 1);
+
+            for expiry in [Expiry::In(0), Expiry::In(-1), Expiry::At(START), Expiry::At(START - 1)] {
+                assert_eq!(
+                    expiry.resolve(kind, START).unwrap_err(),
+                    EscrowError::ExpiryInPast.into()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn expiry_is_capped_at_the_maximum_for_its_kind() {
+        for (kind, max) in [
+            (DeadlineKind::UnixTimestamp, MAX_EXPIRY_DURATION),
+            (DeadlineKind::Slot, MAX_EXPIRY_SLOTS),
+        ] {
+            assert_eq!(kind.max_expiry(), max);
+            assert_eq!(Expiry::In(max).resolve(kind, START).unwrap(), START + max);
+            assert_eq!(Expiry::At(START + max).resolve(kind, START).unwrap(), START + max);
+
+            for expiry in [Expiry::In(max + 1), Expiry::At(START + max + 1)] {
+                assert_eq!(
+                    expiry.resolve(kind, START).unwrap_err(),
+                    EscrowError::ExpiryTooFar.into()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn expiry_reports_overflow() {
+        for kind in KINDS {
+            assert_eq!(
+                Expiry::In(i64::MAX).resolve(kind, START).unwrap_err(),
+                EscrowError::ExpiryOverflow.into()
+            );
+            // the maximum itself can not be computed this close to the end of time
+            assert_eq!(
+                Expiry::At(i64::MAX).resolve(kind, i64::MAX - 1).unwrap_err(),
+                EscrowError::ExpiryOverflow.into()
+            );
+        }
+    }
+
+    #[test]
+    fn extend_can_shorten_down_to_the_minimum_notice() {
+        for kind in KINDS {
+            let notice = kind.min_notice();
+            let escrow = escrow(kind, START, START + 10 * notice);
+
+            assert_eq!(
+                escrow.extended_expiry(Expiry::In(notice), START).unwrap(),
+                START + notice
+            );
+            assert_eq!(
+                escrow.extended_expiry(Expiry::In(notice - 1), START).unwrap_err(),
+                EscrowError::ExpiryNoticeTooShort.into()
+            );
+            // extending is not held to the notice period
+            assert_eq!(
+                escrow.extended_expiry(Expiry::In(20 * notice), START).unwrap(),
+                START + 20 * notice
+            );
+        }
+    }
+
+    #[test]
+    fn extend_keeps_an_unchanged_or_later_expiry_inside_the_notice() {
+        // already within the notice period, moving it later or leaving it is still fine
+        let escrow = escrow(DeadlineKind::UnixTimestamp, START, START + 60);
+
+        assert_eq!(escrow.extended_expiry(Expiry::At(START + 60), START).unwrap(), START + 60);
+        assert_eq!(escrow.extended_expiry(Expiry::In(120), START).unwrap(), START + 120);
+    }
+
+    #[test]
+    fn extend_is_refused_once_expired() {
+        let escrow = escrow(DeadlineKind::UnixTimestamp, START, START + 3_600);
+
+        assert!(escrow.extended_expiry(Expiry::In(3_600), START + 3_599).is_ok());
+        for now in [START + 3_600, START + 7_200] {
+            assert_eq!(
+                escrow.extended_expiry(Expiry::In(3_600), now).unwrap_err(),
+                EscrowError::EscrowExpired.into()
+            );
+        }
+    }
+
+    #[test]
+    fn extend_must_leave_the_start_before_the_expiry() {
+        let escrow = escrow(DeadlineKind::UnixTimestamp, START + 7_200, START + 10_800);
+
+        assert_eq!(
+            escrow.extended_expiry(Expiry::At(START + 7_200), START).unwrap_err(),
+            EscrowError::InvalidStartTime.into()
+        );
+    }
+
+    #[test]
+    fn scheduled_escrow_opens_once_the_clock_reaches_its_start() {
+        let kind = DeadlineKind::UnixTimestamp;
+        let (starts_at, expires_at) =
+            resolve_window(kind, Some(START + 600), Expiry::In(3_600), START).unwrap();
+        let escrow = escrow(kind, starts_at, expires_at);
+
+        // warping the clock across the whole window, from the moment it is made
+        let opened: Vec<bool> = [START, START + 599, START + 600, START + 3_599, START + 3_600]
+            .into_iter()
+            .map(|now| escrow.check_open(&clock_at(0, now)).is_ok())
+            .collect();
+        assert_eq!(opened, [false, false, true, true, false]);
+    }
+}